@@ -16,8 +16,7 @@ pub fn many_scene(c: &mut Criterion) {
     // World
     let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
     let ground_sphere = Sphere::new(Point::new(0.0, -1000.0, 0.0), 1000.0, material_ground);
-    let mut objects: Vec<Arc<dyn Hittable>> = Vec::new();
-    objects.push(Arc::new(ground_sphere));
+    let mut objects: Vec<Arc<dyn Hittable>> = vec![Arc::new(ground_sphere)];
 
     for a in -20..20 {
         for b in -11..11 {
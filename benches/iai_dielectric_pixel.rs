@@ -34,11 +34,12 @@ pub fn iai_dielectric_pixel() {
     let right_sphere = Sphere::new(Point::new(1.0, 0.0, -1.0), 0.5, material_right.clone());
 
     // World
-    let mut objects: Vec<Arc<dyn Hittable>> = Vec::new();
-    objects.push(Arc::new(ground_sphere));
-    objects.push(Arc::new(center_sphere));
-    objects.push(Arc::new(left_sphere));
-    objects.push(Arc::new(right_sphere));
+    let objects: Vec<Arc<dyn Hittable>> = vec![
+        Arc::new(ground_sphere),
+        Arc::new(center_sphere),
+        Arc::new(left_sphere),
+        Arc::new(right_sphere),
+    ];
 
     let node = BVHNode::from_objects(objects);
     let mut world = World::new();
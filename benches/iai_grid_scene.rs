@@ -36,8 +36,7 @@ pub fn iai_grid_scene() {
     );
 
     // World
-    let mut objects: Vec<Arc<dyn Hittable>> = Vec::new();
-    objects.push(Arc::new(ground_sphere));
+    let mut objects: Vec<Arc<dyn Hittable>> = vec![Arc::new(ground_sphere)];
 
     // Create a grid of spheres
     let r = f32::cos(PI / 6.0);
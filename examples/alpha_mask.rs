@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    bvh::BVHNode,
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{AlphaMask, Lambertian, Metal},
+    point::Point,
+    quad::Quad,
+    texture::{CheckeredTexture, NoiseTexture},
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.0, 6.0),
+            Point::new(0.0, 0.3, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let checker = Arc::new(CheckeredTexture::from_color(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let material_ground = Arc::new(Lambertian::from_texture(checker));
+    let fence = Arc::new(AlphaMask::new(
+        Arc::new(Lambertian::new(Color::new(0.6, 0.4, 0.2))),
+        Arc::new(CheckeredTexture::from_color(
+            0.15,
+            Color::black(),
+            Color::white(),
+        )),
+    ));
+    let eaten = Arc::new(AlphaMask::with_threshold(
+        Arc::new(Lambertian::new(Color::new(0.8, 0.2, 0.2))),
+        Arc::new(NoiseTexture::new(3.0)),
+        0.5,
+    ));
+
+    // World. A fence with square holes in front of a metal sphere, and a
+    // sphere whose surface is cut away by noise. The objects are in a BVH,
+    // and their shadows have holes as well.
+    let mut objects = World::new();
+    objects.push(Arc::new(Quad::new(
+        Point::new(-1.0, -0.5, 1.0),
+        Vec3::new(2.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.5, 0.0),
+        fence,
+    )));
+    objects.push(Arc::new(Sphere::new(
+        Point::new(0.0, 0.0, 0.0),
+        0.5,
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.1)),
+    )));
+    objects.push(Arc::new(Sphere::new(Point::new(1.8, 0.3, 0.0), 0.8, eaten)));
+    let mut world = World::new();
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.5, 0.0),
+        1000.0,
+        material_ground,
+    )));
+    world.push(Arc::new(BVHNode::from_objects(objects.into_objects())));
+
+    // Render
+    let file_name = "alpha_mask.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Conductor, DiffuseLight, Lambertian, Material, Metal},
+    point::Point,
+    quad::Quad,
+    texture::SolidColor,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera. Looking down at the spheres, whose tangents run along circles
+    // around their poles.
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 5.0, 4.0),
+            Point::new(0.0, 0.5, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(35.0)
+        .samples_per_pixel(400)
+        .max_depth(50)
+        .background(Color::new(0.01, 0.01, 0.01))
+        .build();
+
+    // Materials. Brushing makes the metal smooth along the grooves and rough
+    // across them, which stretches the highlights across the grooves.
+    let light = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::new(
+        15.0, 15.0, 15.0,
+    )))));
+    let ground = Arc::new(Lambertian::new(Color::new(0.2, 0.2, 0.2)));
+    let steel = Arc::new(Metal::anisotropic(Color::new(0.8, 0.8, 0.8), 0.05, 0.5));
+    let aluminium = Arc::new(Conductor::aluminium(0.0).with_anisotropic_roughness(0.05, 0.5));
+    let polished = Arc::new(Conductor::aluminium(0.3));
+    let plate = Arc::new(Conductor::aluminium(0.0).with_anisotropic_roughness(0.5, 0.05));
+
+    // World
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-10.0, 0.0, 10.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -20.0),
+        ground,
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-3.0, 0.01, 1.0),
+        Vec3::new(6.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -3.0),
+        plate,
+    )));
+    let spheres: [(f32, Arc<dyn Material>); 3] = [(-1.9, steel), (0.0, aluminium), (1.9, polished)];
+    for (x, material) in spheres {
+        world.push(Arc::new(Sphere::new(
+            Point::new(x, 0.8, 0.0),
+            0.8,
+            material,
+        )));
+    }
+    for x in [-2.5, 0.0, 2.5] {
+        world.push(Arc::new(Sphere::new(
+            Point::new(x, 4.0, -1.5),
+            0.3,
+            light.clone(),
+        )));
+    }
+
+    // Render
+    let file_name = "brushed_metal.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
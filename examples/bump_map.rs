@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use image::{Rgb, RgbImage};
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{BumpMap, Conductor, DiffuseLight, Lambertian, NormalMap},
+    point::Point,
+    quad::Quad,
+    texture::{ImageTexture, NoiseTexture, SolidColor},
+    vec3::Vec3,
+};
+
+/// Write a tangent space normal map of a grid of round studs to `path`.
+fn write_stud_normal_map(path: &str) {
+    let size = 256;
+    let studs = 8.0;
+    let image = RgbImage::from_fn(size, size, |x, y| {
+        // The position inside of the cell of the stud, in [-1, 1].
+        let cell = |i: u32| 2.0 * ((i as f32 + 0.5) / size as f32 * studs).fract() - 1.0;
+        let (dx, dy) = (cell(x), -cell(y));
+        let r2 = dx * dx + dy * dy;
+        let normal = if r2 < 0.6 {
+            // The side of a spherical cap.
+            Vec3::new(dx, dy, (1.0 - r2).sqrt()).unit()
+        } else {
+            Vec3::new(0.0, 0.0, 1.0).unit()
+        };
+        let channel = |c: f32| (255.0 * 0.5 * (c + 1.0)) as u8;
+        Rgb([
+            channel(normal.x()),
+            channel(normal.y()),
+            channel(normal.z()),
+        ])
+    });
+    image.save(path).expect("Failed to save normal map.");
+}
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.0, 6.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .background(Color::new(0.1, 0.1, 0.15))
+        .build();
+
+    // Materials
+    let normal_map_path = "stud_normal_map.png";
+    write_stud_normal_map(normal_map_path);
+    let earth = Arc::new(ImageTexture::new("./assets/earthmap.jpg"));
+    let bumpy_earth = Arc::new(BumpMap::new(
+        Arc::new(Lambertian::from_texture(earth.clone())),
+        earth,
+        0.02,
+    ));
+    let hammered = Arc::new(BumpMap::new(
+        Arc::new(Conductor::copper(0.2)),
+        Arc::new(NoiseTexture::new(8.0)),
+        0.03,
+    ));
+    let studded = Arc::new(NormalMap::from_image(
+        Arc::new(Lambertian::new(Color::new(0.7, 0.7, 0.7))),
+        normal_map_path,
+    ));
+    let light = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::new(
+        6.0, 6.0, 6.0,
+    )))));
+
+    // World. An earth whose mountains are bumped by its own texture, a
+    // hammered copper sphere, and a floor with studs from a normal map, all
+    // lit from the side to bring out the bumps.
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-3.0, -0.5, 2.0),
+        Vec3::new(6.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -4.0),
+        studded,
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(-0.8, 0.3, 0.0),
+        0.8,
+        bumpy_earth,
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(1.2, 0.1, 0.5),
+        0.6,
+        hammered,
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-6.0, 0.5, -2.0),
+        Vec3::new(0.0, 0.0, 4.0),
+        Vec3::new(0.0, 3.0, 0.0),
+        light,
+    )));
+
+    // Render
+    let file_name = "bump_map.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
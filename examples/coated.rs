@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Coated, Conductor, Lambertian, Material},
+    point::Point,
+    texture::{CheckeredTexture, NoiseTexture},
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 2.0, 6.0),
+            Point::new(0.0, 0.0, -0.5),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(35.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let checker = Arc::new(CheckeredTexture::from_color(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let material_ground = Arc::new(Lambertian::from_texture(checker));
+    let bases: [Arc<dyn Material>; 4] = [
+        Arc::new(Lambertian::new(Color::new(0.8, 0.1, 0.1))),
+        Arc::new(Lambertian::from_texture(Arc::new(NoiseTexture::new(4.0)))),
+        Arc::new(Conductor::gold(0.5)),
+        Arc::new(Lambertian::new(Color::new(0.9, 0.9, 0.9))),
+    ];
+
+    // World. The back row shows the base materials, the front row the same
+    // materials with a coat. The last coat absorbs blue light and tints the
+    // white base yellow.
+    let mut world = World::new();
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.5, 0.0),
+        1000.0,
+        material_ground,
+    )));
+    for (i, base) in bases.into_iter().enumerate() {
+        let x = -1.8 + 1.2 * i as f32;
+        let coated: Arc<dyn Material> = if i == 3 {
+            Arc::new(Coated::with_absorption(
+                base.clone(),
+                1.5,
+                0.0,
+                Color::new(0.5, 1.0, 8.0),
+                0.1,
+            ))
+        } else {
+            Arc::new(Coated::new(base.clone(), 1.5, 0.05))
+        };
+        world.push(Arc::new(Sphere::new(Point::new(x, 0.0, -1.5), 0.5, base)));
+        world.push(Arc::new(Sphere::new(Point::new(x, 0.0, 0.0), 0.5, coated)));
+    }
+
+    // Render
+    let file_name = "coated.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
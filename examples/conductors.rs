@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Conductor, Lambertian},
+    point::Point,
+    texture::CheckeredTexture,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 2.0, 6.0),
+            Point::new(0.0, 0.3, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let checker = Arc::new(CheckeredTexture::from_color(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let material_ground = Arc::new(Lambertian::from_texture(checker));
+
+    // World. The metals get rougher from back to front.
+    let mut world = World::new();
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.5, 0.0),
+        1000.0,
+        material_ground,
+    )));
+    let presets: [fn(f32) -> Conductor; 4] = [
+        Conductor::gold,
+        Conductor::copper,
+        Conductor::aluminium,
+        Conductor::silver,
+    ];
+    for (i, preset) in presets.iter().enumerate() {
+        for (j, roughness) in [0.0, 0.3, 0.6].iter().enumerate() {
+            let center = Point::new(-1.8 + 1.2 * i as f32, 0.0, -2.0 + 1.5 * j as f32);
+            world.push(Arc::new(Sphere::new(
+                center,
+                0.5,
+                Arc::new(preset(*roughness)),
+            )));
+        }
+    }
+
+    // Render
+    let file_name = "conductors.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    constant_medium::ConstantMedium,
+    csg::Csg,
+    hittable::{Sphere, World},
+    material::{Dielectric, Lambertian},
+    point::Point,
+    quad::Quad,
+    texture::CheckeredTexture,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.5, 4.0),
+            Point::new(0.0, 0.3, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(50.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let checker = Arc::new(CheckeredTexture::from_color(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let material_ground = Arc::new(Lambertian::from_texture(checker));
+    let material_box = Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.1)));
+    let material_hole = Arc::new(Lambertian::new(Color::new(0.1, 0.2, 0.5)));
+    let glass = Arc::new(Dielectric::new(1.5));
+
+    // World
+    let mut world = World::new();
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.5, 0.0),
+        1000.0,
+        material_ground,
+    )));
+
+    // A biconvex lens: the intersection of two large glass spheres.
+    let lens = Csg::intersection(
+        Arc::new(Sphere::new(Point::new(0.0, 0.5, -2.1), 1.5, glass.clone())),
+        Arc::new(Sphere::new(Point::new(0.0, 0.5, 0.1), 1.5, glass.clone())),
+    );
+    world.push(Arc::new(lens));
+
+    // A box with a spherical hole carved out of one of its corners.
+    let cube = Quad::quad_box(
+        Point::new(-2.4, -0.5, -1.8),
+        Point::new(-1.4, 0.5, -0.8),
+        material_box,
+    );
+    let hole = Sphere::new(Point::new(-1.4, 0.5, -0.8), 0.6, material_hole);
+    world.push(Arc::new(Csg::difference(Arc::new(cube), Arc::new(hole))));
+
+    // Smoke filling the union of two overlapping spheres.
+    let blob = Csg::union(
+        Arc::new(Sphere::new(Point::new(1.7, 0.0, -1.3), 0.5, glass.clone())),
+        Arc::new(Sphere::new(Point::new(2.1, 0.3, -1.3), 0.4, glass)),
+    );
+    world.push(Arc::new(ConstantMedium::from_color(
+        Arc::new(blob),
+        2.0,
+        Color::new(0.8, 0.1, 0.1),
+    )));
+
+    // Render
+    let file_name = "csg.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use ray_tracing_weekend::{
+    background::GradientBackground,
     camera::CameraBuilder,
     color::Color,
     hittable::{Sphere, World},
@@ -18,6 +19,10 @@ fn main() {
         .with_orientation(look_from, look_at, vup)
         .fov(20.0)
         .with_defocus(5.0, 4.0)
+        .background(GradientBackground::new(
+            Color::white(),
+            Color::new(0.5, 0.7, 1.0),
+        ))
         .build();
 
     // Materials
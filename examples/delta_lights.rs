@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    light::{DirectionalLight, PointLight, SpotLight},
+    material::{AlphaMask, Lambertian, Metal},
+    point::Point,
+    quad::Quad,
+    texture::CheckeredTexture,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 2.5, 7.0),
+            Point::new(0.0, 0.5, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .background(Color::new(0.02, 0.02, 0.03))
+        .build();
+
+    // Materials
+    let ground = Arc::new(Lambertian::new(Color::new(0.6, 0.6, 0.6)));
+    let fence = Arc::new(AlphaMask::new(
+        Arc::new(Lambertian::new(Color::new(0.6, 0.4, 0.2))),
+        Arc::new(CheckeredTexture::from_color(
+            0.15,
+            Color::black(),
+            Color::white(),
+        )),
+    ));
+
+    // World. Lights without a surface cast crisp shadows, which have holes
+    // where they pass through the transparent parts of the fence.
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-10.0, 0.0, 10.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -20.0),
+        ground,
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-2.5, 0.0, -1.0),
+        Vec3::new(2.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.5, 0.0),
+        fence,
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, 0.6, 0.5),
+        0.6,
+        Arc::new(Lambertian::new(Color::new(0.8, 0.3, 0.2))),
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(1.8, 0.6, 0.0),
+        0.6,
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.3)),
+    )));
+
+    // Lights. A low sun from the front right, a warm point light on the
+    // left, and a blue spotlight from above.
+    world.push_light(Arc::new(DirectionalLight::new(
+        Vec3::new(-1.0, -1.0, -1.5),
+        Color::new(1.5, 1.4, 1.2),
+    )));
+    world.push_light(Arc::new(PointLight::new(
+        Point::new(-3.0, 2.5, 2.0),
+        Color::new(6.0, 4.0, 2.0),
+    )));
+    world.push_light(Arc::new(SpotLight::new(
+        Point::new(1.8, 4.0, 1.5),
+        Point::new(1.8, 0.0, 0.0),
+        Color::new(4.0, 6.0, 16.0),
+        25.0,
+        15.0,
+    )));
+
+    // Render
+    let file_name = "delta_lights.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
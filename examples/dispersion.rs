@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Dielectric, DiffuseLight, Lambertian, RefractiveIndex},
+    point::Point,
+    quad::Quad,
+    texture::{CheckeredTexture, SolidColor},
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.0, 5.0),
+            Point::new(0.0, 0.3, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let checker = Arc::new(CheckeredTexture::from_color(
+        0.25,
+        Color::new(0.1, 0.1, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let material_checker = Arc::new(Lambertian::from_texture(checker));
+    let light = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::new(
+        4.0, 4.0, 4.0,
+    )))));
+
+    // World. A checkered floor and wall behind spheres of increasingly
+    // dispersive glass, which split the light into rainbow colored fringes.
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-5.0, -0.5, -3.0),
+        Vec3::new(10.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 8.0),
+        material_checker.clone(),
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-5.0, -0.5, -1.5),
+        Vec3::new(10.0, 0.0, 0.0),
+        Vec3::new(0.0, 5.0, 0.0),
+        material_checker,
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-2.0, 3.0, -1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 2.0),
+        light,
+    )));
+    let refraction_indices = [
+        RefractiveIndex::fused_silica(),
+        RefractiveIndex::bk7(),
+        RefractiveIndex::dense_flint(),
+        RefractiveIndex::diamond(),
+    ];
+    for (i, refraction_index) in refraction_indices.into_iter().enumerate() {
+        world.push(Arc::new(Sphere::new(
+            Point::new(-1.65 + 1.1 * i as f32, 0.0, 0.0),
+            0.5,
+            Arc::new(Dielectric::dispersive(refraction_index)),
+        )));
+    }
+
+    // Render
+    let file_name = "dispersion.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
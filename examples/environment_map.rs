@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use image::{Rgb, Rgb32FImage};
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    environment::EnvironmentMap,
+    hittable::{Sphere, World},
+    material::{Dielectric, Lambertian, Metal},
+    point::Point,
+    quad::Quad,
+    vec3::Vec3,
+    PI,
+};
+
+/// A simple sky with a small, bright sun, for when no HDR image is given.
+fn synthetic_sky() -> Rgb32FImage {
+    let (width, height) = (512, 256);
+    Rgb32FImage::from_fn(width, height, |x, y| {
+        let u = x as f32 / width as f32;
+        let theta = PI * (y as f32 + 0.5) / height as f32;
+        let elevation = 0.5 * PI - theta;
+        if (u - 0.3).abs() < 0.004 && (elevation - 0.5).abs() < 0.008 {
+            Rgb([5000.0, 4600.0, 4000.0])
+        } else if elevation > 0.0 {
+            let t = elevation.sin();
+            Rgb([0.8 - 0.5 * t, 0.9 - 0.4 * t, 1.0])
+        } else {
+            Rgb([0.3, 0.25, 0.2])
+        }
+    })
+}
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.5, 6.0),
+            Point::new(0.0, 0.6, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let diffuse = Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.2)));
+    let glass = Arc::new(Dielectric::new(1.5));
+    let metal = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0));
+
+    // World. The scene is lit only by the environment, which is loaded from
+    // the `.hdr` or `.exr` file given as the first argument.
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-10.0, 0.0, 10.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -20.0),
+        ground,
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(-1.6, 0.7, 0.0),
+        0.7,
+        diffuse,
+    )));
+    world.push(Arc::new(Sphere::new(Point::new(0.0, 0.7, 0.0), 0.7, glass)));
+    world.push(Arc::new(Sphere::new(Point::new(1.6, 0.7, 0.0), 0.7, metal)));
+    let environment = match std::env::args().nth(1) {
+        Some(path) => EnvironmentMap::new(path),
+        None => EnvironmentMap::from_image(synthetic_sky()),
+    };
+    world.set_environment(Arc::new(environment.with_rotation(30.0)));
+
+    // Render
+    let file_name = "environment_map.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
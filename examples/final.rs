@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use ray_tracing_weekend::{
+    background::GradientBackground,
     bvh::BVHNode,
     camera::CameraBuilder,
     color::Color,
@@ -15,8 +16,7 @@ fn main() {
     // World
     let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
     let ground_sphere = Sphere::new(Point::new(0.0, -1000.0, 0.0), 1000.0, material_ground);
-    let mut objects: Vec<Arc<dyn Hittable>> = Vec::new();
-    objects.push(Arc::new(ground_sphere));
+    let mut objects: Vec<Arc<dyn Hittable>> = vec![Arc::new(ground_sphere)];
 
     for a in -11..11 {
         for b in -11..11 {
@@ -73,6 +73,10 @@ fn main() {
             Vec3::new(0.0, 1.0, 0.0),
         )
         .with_defocus(0.6, 10.0)
+        .background(GradientBackground::new(
+            Color::white(),
+            Color::new(0.5, 0.7, 1.0),
+        ))
         .build();
 
     let node = BVHNode::from_objects(objects);
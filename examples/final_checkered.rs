@@ -21,8 +21,7 @@ fn main() {
         1000.0,
         Arc::new(Lambertian::from_texture(Arc::new(material_ground))),
     );
-    let mut objects: Vec<Arc<dyn Hittable>> = Vec::new();
-    objects.push(Arc::new(ground_sphere));
+    let mut objects: Vec<Arc<dyn Hittable>> = vec![Arc::new(ground_sphere)];
 
     for a in -11..11 {
         for b in -11..11 {
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use ray_tracing_weekend::{
+    background::GradientBackground,
     camera::CameraBuilder,
     color::Color,
     hittable::{Sphere, World},
@@ -11,7 +12,13 @@ use ray_tracing_weekend::{
 
 fn main() {
     // Default camera
-    let camera = CameraBuilder::default().fov(100.0).build();
+    let camera = CameraBuilder::default()
+        .fov(100.0)
+        .background(GradientBackground::new(
+            Color::white(),
+            Color::new(0.5, 0.7, 1.0),
+        ))
+        .build();
 
     // Materials
     let yellow_lambertian = Arc::new(Lambertian::new(Color::new(0.0, 0.0, 1.0)));
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use ray_tracing_weekend::{
+    background::GradientBackground,
     camera::CameraBuilder,
     color::Color,
     hittable::{Sphere, World},
@@ -10,7 +11,12 @@ use ray_tracing_weekend::{
 
 fn main() {
     // Default camera
-    let camera = CameraBuilder::default().build();
+    let camera = CameraBuilder::default()
+        .background(GradientBackground::new(
+            Color::white(),
+            Color::new(0.5, 0.7, 1.0),
+        ))
+        .build();
 
     // Materials
     let material_ground = Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.0)));
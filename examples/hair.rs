@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    bvh::BVHNode,
+    camera::CameraBuilder,
+    color::Color,
+    curve::{Curve, CurveType},
+    hittable::{Hittable, Sphere, World},
+    material::{Hair, Lambertian},
+    point::Point,
+    random_0_1_f32, random_unit_vector,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 0.5, 3.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(50)
+        .max_depth(20)
+        .build();
+
+    // Materials
+    let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_skin = Arc::new(Lambertian::new(Color::new(0.3, 0.15, 0.05)));
+    let material_fur = Arc::new(Hair::new(
+        Color::new(0.6, 0.35, 0.1),
+        Color::new(0.3, 0.3, 0.3),
+        40.0,
+    ));
+
+    // A ball of fur. Each hair grows out of the sphere and bends downwards.
+    let center = Point::new(0.0, 0.0, 0.0);
+    let radius = 0.5;
+    let mut hairs: Vec<Arc<dyn Hittable>> =
+        vec![Arc::new(Sphere::new(center, radius, material_skin))];
+    for _ in 0..20000 {
+        let normal = *random_unit_vector();
+        let length = 0.15 + 0.1 * random_0_1_f32();
+        let gravity = Vec3::new(0.0, -0.5 * length, 0.0);
+        let root = center + radius * normal;
+        let control_points = [
+            root,
+            root + (length / 3.0) * normal,
+            root + (2.0 * length / 3.0) * normal + 0.5 * gravity,
+            root + length * normal + gravity,
+        ];
+        hairs.push(Arc::new(Curve::new(
+            control_points,
+            0.006,
+            0.001,
+            CurveType::Cylinder,
+            material_fur.clone(),
+        )));
+    }
+
+    // World
+    let mut world = World::new();
+    world.push(Arc::new(BVHNode::from_objects(hairs)));
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -100.75, 0.0),
+        100.0,
+        material_ground,
+    )));
+
+    // Render
+    let file_name = "hair.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
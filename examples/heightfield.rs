@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder, heightfield::Heightfield, hittable::World, material::Lambertian,
+    point::Point, texture::ImageTexture, vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let look_from = Point::new(0.0, 6.0, 9.0);
+    let look_at = Point::new(0.0, 0.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+    let camera = CameraBuilder::default()
+        .with_orientation(look_from, look_at, vup)
+        .fov(45.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let earth_texture = Arc::new(ImageTexture::new("./assets/earthmap.jpg"));
+    let earth_surface = Arc::new(Lambertian::from_texture(earth_texture));
+
+    // World. The brightness of the earth map doubles as the heightmap.
+    let terrain = Heightfield::new(
+        "./assets/earthmap.jpg",
+        Point::new(-8.0, 0.0, -4.0),
+        Vec3::new(16.0, 0.5, 8.0),
+        earth_surface,
+    );
+    let mut world = World::new();
+    world.push(Arc::new(terrain));
+
+    // Render
+    let file_name = "heightfield.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
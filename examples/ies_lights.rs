@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::World,
+    ies::IesProfile,
+    light::PointLight,
+    material::{DiffuseLight, Lambertian},
+    point::Point,
+    quad::Quad,
+    vec3::Vec3,
+};
+
+/// A downlight with a rotationally symmetric batwing distribution, which is
+/// brightest about 30 degrees off its nadir.
+const DOWNLIGHT: &str = "IESNA:LM-63-2002
+[TEST] Batwing downlight
+[MANUFAC] Example
+TILT=NONE
+1 1000 1.0 10 1 1 2 0.1 0.1 0
+1.0 1.0 20
+0 10 20 30 40 50 60 70 80 90
+0
+400 600 900 1000 700 300 100 30 5 0
+";
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 3.0, 10.0),
+            Point::new(0.0, 2.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(45.0)
+        .samples_per_pixel(400)
+        .max_depth(50)
+        .background(Color::black())
+        .build();
+
+    // Materials
+    let white = Arc::new(Lambertian::new(Color::new(0.6, 0.6, 0.6)));
+    let profile = Arc::new(IesProfile::parse(DOWNLIGHT).expect("Malformed IES file."));
+
+    // World. Three downlights close to the wall draw the typical scallops,
+    // and a panel with the same profile lights the floor in front.
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-10.0, 0.0, 10.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -20.0),
+        white.clone(),
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-10.0, 0.0, -2.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 10.0, 0.0),
+        white,
+    )));
+    for x in [-3.5, 0.0, 3.5] {
+        world.push_light(Arc::new(
+            PointLight::new(Point::new(x, 4.5, -1.5), Color::new(20.0, 18.0, 15.0))
+                .with_profile(profile.clone()),
+        ));
+    }
+    world.push(Arc::new(Quad::new(
+        Point::new(-1.0, 4.5, 4.0),
+        Vec3::new(0.0, 0.0, -2.0),
+        Vec3::new(2.0, 0.0, 0.0),
+        Arc::new(
+            DiffuseLight::from_color(Color::new(1.5, 1.5, 1.5))
+                .one_sided()
+                .with_profile(profile),
+        ),
+    )));
+
+    // Render
+    let file_name = "ies_lights.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    bvh::BVHNode,
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    light::Light,
+    material::{DiffuseLight, Lambertian},
+    point::Point,
+    quad::Quad,
+    random_0_1_f32, random_f32,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(-6.0, 9.0, 22.0),
+            Point::new(2.0, 2.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(50.0)
+        .samples_per_pixel(200)
+        .max_depth(10)
+        .background(Color::new(0.01, 0.01, 0.03))
+        .build();
+
+    // Materials
+    let street = Arc::new(Lambertian::new(Color::new(0.2, 0.2, 0.22)));
+    let facade = Arc::new(Lambertian::new(Color::new(0.45, 0.42, 0.4)));
+    let warm = Arc::new(DiffuseLight::from_color(Color::new(6.0, 4.5, 2.5)).one_sided());
+    let cool = Arc::new(DiffuseLight::from_color(Color::new(3.0, 4.0, 6.0)).one_sided());
+    let lamp = Arc::new(DiffuseLight::from_color(Color::new(40.0, 30.0, 15.0)));
+
+    // World. A grid of blocks whose windows facing the streets are lit at
+    // random, and lamps along the streets. Every window and lamp is both an
+    // object and a light.
+    let mut objects = World::new();
+    let mut lights: Vec<Arc<dyn Light>> = Vec::new();
+    objects.push(Arc::new(Quad::new(
+        Point::new(-40.0, 0.0, 40.0),
+        Vec3::new(80.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -80.0),
+        street,
+    )));
+    for i in -4..4 {
+        for j in -6..2 {
+            let (x, z) = (i as f32 * 5.0, j as f32 * 5.0);
+            let floors = random_f32(2.0, 8.0) as i32;
+            let height = floors as f32 * 1.2;
+            objects.extend(
+                Quad::quad_box(
+                    Point::new(x, 0.0, z - 3.0),
+                    Point::new(x + 3.0, height, z),
+                    facade.clone(),
+                )
+                .into_objects(),
+            );
+
+            // Windows on the front and on the side towards the camera.
+            for floor in 0..floors {
+                let y = floor as f32 * 1.2 + 0.4;
+                for k in 0..3 {
+                    let offset = 0.25 + k as f32;
+                    let windows = [
+                        (
+                            Point::new(x + offset, y, z + 0.01),
+                            Vec3::new(0.5, 0.0, 0.0),
+                        ),
+                        (
+                            Point::new(x - 0.01, y, z - offset - 0.5),
+                            Vec3::new(0.0, 0.0, 0.5),
+                        ),
+                    ];
+                    for (q, u) in windows {
+                        if random_0_1_f32() < 0.5 {
+                            continue;
+                        }
+                        let light = if random_0_1_f32() < 0.8 {
+                            warm.clone()
+                        } else {
+                            cool.clone()
+                        };
+                        let window = Arc::new(Quad::new(q, u, Vec3::new(0.0, 0.6, 0.0), light));
+                        objects.push(window.clone());
+                        lights.push(window);
+                    }
+                }
+            }
+        }
+    }
+    for i in -4..4 {
+        for j in -6..2 {
+            let center = Point::new(i as f32 * 5.0 - 1.0, 1.5, j as f32 * 5.0 + 1.0);
+            let lamp = Arc::new(Sphere::new(center, 0.12, lamp.clone()));
+            objects.push(lamp.clone());
+            lights.push(lamp);
+        }
+    }
+
+    let mut world = World::new();
+    world.push(Arc::new(BVHNode::from_objects(objects.into_objects())));
+    for light in lights {
+        world.push_light(light);
+    }
+
+    // Render
+    let file_name = "many_lights.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
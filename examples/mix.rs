@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Conductor, Dielectric, Lambertian, MixMaterial},
+    point::Point,
+    texture::{CheckeredTexture, NoiseTexture},
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.5, 5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(35.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let checker = Arc::new(CheckeredTexture::from_color(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let material_ground = Arc::new(Lambertian::from_texture(checker));
+    let rust = Arc::new(Lambertian::new(Color::new(0.45, 0.2, 0.08)));
+    let steel = Arc::new(Conductor::new(
+        Color::new(2.9, 2.9, 2.9),
+        Color::new(3.0, 3.0, 3.0),
+        0.2,
+    ));
+    let glass = Arc::new(Dielectric::new(1.5));
+    let blue = Arc::new(Lambertian::new(Color::new(0.1, 0.2, 0.7)));
+
+    // World. A rusty steel sphere where marble-like noise decides where the
+    // rust is, a sphere that is half glass and half blue, and one where a
+    // checker pattern switches between glass and rust.
+    let mut world = World::new();
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.5, 0.0),
+        1000.0,
+        material_ground,
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(-1.2, 0.0, 0.0),
+        0.5,
+        Arc::new(MixMaterial::new(
+            steel,
+            rust.clone(),
+            Arc::new(NoiseTexture::new(6.0)),
+        )),
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, 0.0, 0.0),
+        0.5,
+        Arc::new(MixMaterial::from_weight(glass.clone(), blue, 0.5)),
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(1.2, 0.0, 0.0),
+        0.5,
+        Arc::new(MixMaterial::new(
+            glass,
+            rust,
+            Arc::new(CheckeredTexture::from_color(
+                0.1,
+                Color::black(),
+                Color::white(),
+            )),
+        )),
+    )));
+
+    // Render
+    let file_name = "mix.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{DiffuseLight, Lambertian, OrenNayar},
+    point::Point,
+    quad::Quad,
+    texture::{ImageTexture, SolidColor},
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 0.0, 6.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(30.0)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .background(Color::new(0.0, 0.0, 0.0))
+        .build();
+
+    // Materials
+    let light = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::new(
+        3.0, 3.0, 3.0,
+    )))));
+    let earth = Arc::new(ImageTexture::new("./assets/earthmap.jpg"));
+
+    // World. Spheres lit from behind the camera. The lambertian sphere on the
+    // left darkens towards its edge, while the rougher Oren-Nayar spheres to
+    // its right look increasingly flat. The bottom row shows the same with a
+    // texture.
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-4.0, -4.0, 12.0),
+        Vec3::new(8.0, 0.0, 0.0),
+        Vec3::new(0.0, 8.0, 0.0),
+        light,
+    )));
+    let gray = Color::new(0.7, 0.7, 0.7);
+    world.push(Arc::new(Sphere::new(
+        Point::new(-1.8, 0.6, 0.0),
+        0.5,
+        Arc::new(Lambertian::new(gray)),
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(-1.8, -0.6, 0.0),
+        0.5,
+        Arc::new(Lambertian::from_texture(earth.clone())),
+    )));
+    for (i, sigma) in [20.0, 40.0, 60.0].iter().enumerate() {
+        let x = -0.6 + 1.2 * i as f32;
+        world.push(Arc::new(Sphere::new(
+            Point::new(x, 0.6, 0.0),
+            0.5,
+            Arc::new(OrenNayar::new(gray, *sigma)),
+        )));
+        world.push(Arc::new(Sphere::new(
+            Point::new(x, -0.6, 0.0),
+            0.5,
+            Arc::new(OrenNayar::from_texture(earth.clone(), *sigma)),
+        )));
+    }
+
+    // Render
+    let file_name = "oren_nayar.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
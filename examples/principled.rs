@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::Lambertian,
+    point::Point,
+    principled::PrincipledBuilder,
+    texture::CheckeredTexture,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 3.0, 7.0),
+            Point::new(0.0, 0.0, -0.5),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let checker = Arc::new(CheckeredTexture::from_color(
+        0.5,
+        Color::new(0.2, 0.2, 0.2),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let material_ground = Arc::new(Lambertian::from_texture(checker));
+    let red = Color::new(0.8, 0.1, 0.1);
+    let gold = Color::new(1.0, 0.71, 0.29);
+
+    // World. The back row blends from a red plastic to a gold metal, the
+    // middle row gets rougher from left to right, and the front row shows
+    // sheen, clearcoat, anisotropy, glass, and a textured metallic parameter.
+    let mut world = World::new();
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.5, 0.0),
+        1000.0,
+        material_ground,
+    )));
+    let mut push = |i: usize, row: usize, builder: &PrincipledBuilder| {
+        let center = Point::new(-2.4 + 1.2 * i as f32, 0.0, -2.0 + 1.5 * row as f32);
+        world.push(Arc::new(Sphere::new(
+            center,
+            0.5,
+            Arc::new(builder.build()),
+        )));
+    };
+    for i in 0..5 {
+        let t = i as f32 / 4.0;
+        let base_color = red * (1.0 - t) + gold * t;
+        push(
+            i,
+            0,
+            PrincipledBuilder::new()
+                .base_color(base_color)
+                .metallic(t)
+                .roughness(0.3),
+        );
+        push(
+            i,
+            1,
+            PrincipledBuilder::new()
+                .base_color(gold)
+                .metallic(1.0)
+                .roughness(t * 0.8),
+        );
+    }
+    push(
+        0,
+        2,
+        PrincipledBuilder::new()
+            .base_color(Color::new(0.2, 0.1, 0.4))
+            .roughness(0.9)
+            .sheen(1.0),
+    );
+    push(
+        1,
+        2,
+        PrincipledBuilder::new()
+            .base_color(red)
+            .roughness(0.6)
+            .clearcoat(1.0),
+    );
+    push(
+        2,
+        2,
+        PrincipledBuilder::new()
+            .base_color(Color::new(0.9, 0.9, 0.9))
+            .metallic(1.0)
+            .roughness(0.5)
+            .anisotropic(1.0),
+    );
+    push(
+        3,
+        2,
+        PrincipledBuilder::new()
+            .base_color(Color::new(0.8, 1.0, 0.9))
+            .roughness(0.1)
+            .transmission(1.0),
+    );
+    push(
+        4,
+        2,
+        PrincipledBuilder::new()
+            .base_color(gold)
+            .roughness(0.3)
+            .metallic_texture(Arc::new(CheckeredTexture::from_color(
+                0.2,
+                Color::black(),
+                Color::white(),
+            ))),
+    );
+
+    // Render
+    let file_name = "principled.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
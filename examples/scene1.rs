@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use ray_tracing_weekend::{
+    background::GradientBackground,
     bvh::BVHNode,
     camera::CameraBuilder,
     color::Color,
@@ -11,7 +12,12 @@ use ray_tracing_weekend::{
 
 fn main() {
     // Default camera
-    let camera = CameraBuilder::default().build();
+    let camera = CameraBuilder::default()
+        .background(GradientBackground::new(
+            Color::white(),
+            Color::new(0.5, 0.7, 1.0),
+        ))
+        .build();
 
     // Materials
     let yellow_lambertian = Arc::new(Lambertian::new(Color::new(1.0, 0.02, 0.02)));
@@ -26,9 +32,7 @@ fn main() {
     );
 
     // World
-    let mut objects: Vec<Arc<dyn Hittable>> = Vec::new();
-    objects.push(Arc::new(sphere1));
-    objects.push(Arc::new(sphere2));
+    let objects: Vec<Arc<dyn Hittable>> = vec![Arc::new(sphere1), Arc::new(sphere2)];
 
     let node = BVHNode::from_objects(objects);
     let mut world = World::new();
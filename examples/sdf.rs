@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    aabb::AABB,
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Dielectric, Lambertian, Metal},
+    point::Point,
+    sdf::{Mandelbulb, RoundedBox, SdfShape, SdfSphere, SmoothUnion, Torus, Twist},
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.5, 4.0),
+            Point::new(0.0, 0.2, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(50.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_box = Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.1)));
+    let material_blob = Arc::new(Dielectric::new(1.5));
+    let material_bulb = Arc::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.1));
+
+    // World
+    let mut world = World::new();
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.5, 0.0),
+        1000.0,
+        material_ground,
+    )));
+
+    // A twisted rounded box.
+    let twisted_box = Twist::new(
+        Arc::new(RoundedBox::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.3, 0.5, 0.3),
+            0.05,
+        )),
+        1.5,
+    );
+    world.push(Arc::new(SdfShape::new(
+        Arc::new(twisted_box),
+        AABB::from_points(Point::new(-0.5, -0.5, -0.5), Point::new(0.5, 0.5, 0.5)),
+        material_box,
+    )));
+
+    // A torus smoothly blended with a sphere.
+    let blob = SmoothUnion::new(
+        Arc::new(Torus::new(Point::new(-1.6, -0.2, -1.0), 0.4, 0.15)),
+        Arc::new(SdfSphere::new(Point::new(-1.6, 0.1, -1.0), 0.25)),
+        0.2,
+    );
+    world.push(Arc::new(SdfShape::new(
+        Arc::new(blob),
+        AABB::from_points(Point::new(-2.2, -0.5, -1.6), Point::new(-1.0, 0.5, -0.4)),
+        material_blob,
+    )));
+
+    // The Mandelbulb fractal.
+    let bulb = Mandelbulb::new(Point::new(1.6, 0.1, -1.0), 0.5, 8.0, 10);
+    world.push(Arc::new(SdfShape::new(
+        Arc::new(bulb),
+        AABB::from_points(Point::new(1.0, -0.5, -1.6), Point::new(2.2, 0.7, -0.4)),
+        material_bulb,
+    )));
+
+    // Render
+    let file_name = "sdf.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
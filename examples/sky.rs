@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Dielectric, Lambertian, Metal},
+    point::Point,
+    quad::Quad,
+    sky::Sky,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(-2.0, 1.5, 7.0),
+            Point::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(45.0)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let ground = Arc::new(Lambertian::new(Color::new(0.4, 0.4, 0.35)));
+    let wall = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.45)));
+    let glass = Arc::new(Dielectric::new(1.5));
+    let metal = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0));
+
+    // World. A late afternoon sun, low in the west, behind the camera on the
+    // left, which casts long, warm shadows under a blue sky.
+    let mut world = World::new();
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.0, 0.0),
+        1000.0,
+        ground,
+    )));
+    world.push(Arc::new(Quad::quad_box(
+        Point::new(-2.5, 0.0, -1.5),
+        Point::new(-0.5, 2.5, -0.5),
+        wall,
+    )));
+    world.push(Arc::new(Sphere::new(Point::new(0.7, 0.7, 0.5), 0.7, glass)));
+    world.push(Arc::new(Sphere::new(
+        Point::new(2.4, 0.7, -0.5),
+        0.7,
+        metal,
+    )));
+    let sky = Sky::new(20.0, 240.0, 3.0);
+    world.set_environment(Arc::new(sky.to_environment(512)));
+    world.push_light(Arc::new(sky.sun()));
+
+    // Render
+    let file_name = "sky.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
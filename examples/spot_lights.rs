@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::World,
+    material::{DiffuseLight, Lambertian},
+    point::Point,
+    quad::Quad,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 10.0, 13.0),
+            Point::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(45.0)
+        .samples_per_pixel(400)
+        .max_depth(50)
+        .background(Color::new(0.0, 0.0, 0.0))
+        .build();
+
+    // Materials. Three lights above the floor, whose front faces point
+    // down. From left to right: a plain diffuse light, a light focused with a
+    // cosine power, and a spotlight.
+    let white = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let color = Color::new(20.0, 20.0, 16.0);
+    let lights = [
+        DiffuseLight::from_color(color),
+        DiffuseLight::from_color(color)
+            .one_sided()
+            .with_cosine_power(20.0),
+        DiffuseLight::from_color(color)
+            .one_sided()
+            .with_spot(20.0, 10.0),
+    ];
+
+    // World
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-10.0, 0.0, 10.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -20.0),
+        white.clone(),
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-10.0, 0.0, -3.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 10.0, 0.0),
+        white,
+    )));
+    for (i, light) in lights.into_iter().enumerate() {
+        let x = -6.75 + 5.0 * i as f32;
+        world.push(Arc::new(Quad::new(
+            Point::new(x, 4.0, -0.75),
+            Vec3::new(1.5, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.5),
+            Arc::new(light),
+        )));
+    }
+    // A one-sided light facing the wall, which is dark from the front.
+    world.push(Arc::new(Quad::new(
+        Point::new(3.0, 0.5, 1.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Arc::new(DiffuseLight::from_color(color).one_sided()),
+    )));
+
+    // Render
+    let file_name = "spot_lights.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
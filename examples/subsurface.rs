@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{RotationY, Sphere, Translate, World},
+    material::{DiffuseLight, Lambertian},
+    point::Point,
+    quad::Quad,
+    subsurface::Subsurface,
+    texture::SolidColor,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.5, 7.0),
+            Point::new(0.0, 0.4, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(32.0)
+        .samples_per_pixel(400)
+        .max_depth(50)
+        .background(Color::new(0.02, 0.02, 0.03))
+        .build();
+
+    // Materials
+    let light = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::new(
+        8.0, 8.0, 8.0,
+    )))));
+    let ground = Arc::new(Lambertian::new(Color::new(0.4, 0.4, 0.4)));
+    let white = Arc::new(Lambertian::new(Color::new(0.9, 0.9, 0.9)));
+
+    // World. A lambertian sphere on the left for comparison, followed by wax,
+    // skin, and a block of marble. The light is behind the objects, such that
+    // it bleeds through their thin parts.
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-10.0, 0.0, 10.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -20.0),
+        ground,
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-3.0, 3.5, -3.5),
+        Vec3::new(6.0, 0.0, 0.0),
+        Vec3::new(0.0, -1.0, -1.0),
+        light,
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(-2.4, 0.6, 0.0),
+        0.6,
+        white.clone(),
+    )));
+    let wax = Sphere::new(Point::new(-0.8, 0.6, 0.0), 0.6, white.clone());
+    world.push(Arc::new(Subsurface::new(
+        Arc::new(wax),
+        Color::new(0.2, 0.2, 0.15),
+        Color::new(0.999, 0.99, 0.96),
+    )));
+    let skin = Sphere::new(Point::new(0.8, 0.6, 0.0), 0.6, white.clone());
+    world.push(Arc::new(Subsurface::new(
+        Arc::new(skin),
+        Color::new(0.25, 0.12, 0.08),
+        Color::new(0.995, 0.96, 0.93),
+    )));
+    let marble = Quad::quad_box(
+        Point::new(-0.5, 0.0, -0.5),
+        Point::new(0.5, 1.0, 0.5),
+        white,
+    );
+    let marble = RotationY::new(Arc::new(marble), 30.0);
+    let marble = Translate::new(Arc::new(marble), Vec3::new(2.4, 0.0, 0.0));
+    world.push(Arc::new(Subsurface::new(
+        Arc::new(marble),
+        Color::new(0.08, 0.08, 0.08),
+        Color::new(0.995, 0.995, 0.99),
+    )));
+
+    // Render
+    let file_name = "subsurface.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
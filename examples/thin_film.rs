@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Dielectric, Metal},
+    point::Point,
+    quad::Quad,
+    texture::NoiseTexture,
+    thin_film::ThinFilm,
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.5, 6.0),
+            Point::new(0.0, 0.6, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(35.0)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .build();
+
+    // Materials. The bubbles are films of water around air whose thickness
+    // varies with noise. The ground is a dark metal under a film of oil, and
+    // the sphere on the right is a metal with a thin oxide layer.
+    let bubble = Arc::new(Dielectric::new(1.0).with_thin_film(ThinFilm::from_texture(
+        Arc::new(NoiseTexture::new(1.5)),
+        900.0,
+        1.33,
+    )));
+    let oil = Arc::new(Metal::new(Color::new(0.1, 0.1, 0.1), 0.0).with_thin_film(
+        ThinFilm::from_texture(Arc::new(NoiseTexture::new(1.0)), 700.0, 1.45),
+    ));
+    let oxide = Arc::new(
+        Metal::new(Color::new(0.6, 0.6, 0.6), 0.0).with_thin_film(ThinFilm::new(320.0, 2.4)),
+    );
+
+    // World
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-20.0, 0.0, 20.0),
+        Vec3::new(40.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -40.0),
+        oil,
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(-1.3, 0.9, 0.0),
+        0.9,
+        bubble.clone(),
+    )));
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.1, 1.6, 1.2),
+        0.4,
+        bubble,
+    )));
+    world.push(Arc::new(Sphere::new(Point::new(1.3, 0.8, 0.0), 0.8, oxide)));
+
+    // Render
+    let file_name = "thin_film.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use ray_tracing_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    hittable::{Sphere, World},
+    material::{Dielectric, DiffuseLight, Lambertian, RoughDielectric},
+    point::Point,
+    quad::Quad,
+    texture::{CheckeredTexture, SolidColor},
+    vec3::Vec3,
+};
+
+fn main() {
+    // Camera
+    let camera = CameraBuilder::default()
+        .with_orientation(
+            Point::new(0.0, 1.0, 5.0),
+            Point::new(0.0, 0.3, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .fov(40.0)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .build();
+
+    // Materials
+    let checker = Arc::new(CheckeredTexture::from_color(
+        0.25,
+        Color::new(0.1, 0.1, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let material_checker = Arc::new(Lambertian::from_texture(checker));
+    let light = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::new(
+        4.0, 4.0, 4.0,
+    )))));
+
+    // World. A checkered floor and wall behind tinted glass. The spheres
+    // get thicker from left to right, and the thicker ones absorb more light.
+    let mut world = World::new();
+    world.push(Arc::new(Quad::new(
+        Point::new(-5.0, -0.5, -3.0),
+        Vec3::new(10.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 8.0),
+        material_checker.clone(),
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-5.0, -0.5, -1.5),
+        Vec3::new(10.0, 0.0, 0.0),
+        Vec3::new(0.0, 5.0, 0.0),
+        material_checker,
+    )));
+    world.push(Arc::new(Quad::new(
+        Point::new(-2.0, 3.0, -1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 2.0),
+        light,
+    )));
+    let green = Color::new(0.2, 0.8, 0.3);
+    for (i, radius) in [0.2, 0.3, 0.4, 0.5].iter().enumerate() {
+        world.push(Arc::new(Sphere::new(
+            Point::new(-1.65 + 1.1 * i as f32, radius - 0.5, 0.0),
+            *radius,
+            Arc::new(Dielectric::tinted(1.5, green, 0.5)),
+        )));
+    }
+    world.push(Arc::new(Sphere::new(
+        Point::new(0.0, 1.6, -0.5),
+        0.4,
+        Arc::new(RoughDielectric::tinted(
+            1.5,
+            0.2,
+            Color::new(0.9, 0.3, 0.2),
+            0.5,
+        )),
+    )));
+
+    // Render
+    let file_name = "tinted_glass.png";
+    let image = camera.render(&world);
+    image.save(file_name).expect("Failed to save file.");
+}
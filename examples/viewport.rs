@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use ray_tracing_weekend::{
+    background::GradientBackground,
     camera::CameraBuilder,
     color::Color,
     hittable::{Sphere, World},
@@ -17,6 +18,10 @@ fn main() {
     let camera = CameraBuilder::default()
         .with_orientation(look_from, look_at, vup)
         .fov(40.0)
+        .background(GradientBackground::new(
+            Color::white(),
+            Color::new(0.5, 0.7, 1.0),
+        ))
         .build();
 
     // Materials
@@ -94,6 +94,14 @@ impl AABB {
         Self { x, y, z }
     }
 
+    /// Create a new bounding box that contains the overlap of `box1` and `box2`.
+    pub fn from_overlap(box1: &AABB, box2: &AABB) -> Self {
+        let x = Interval::overlapping(&box1.x, &box2.x);
+        let y = Interval::overlapping(&box1.y, &box2.y);
+        let z = Interval::overlapping(&box1.z, &box2.z);
+        Self { x, y, z }
+    }
+
     #[inline]
     /// Get the x interval.
     pub fn x(&self) -> &Interval {
@@ -127,6 +135,26 @@ impl AABB {
         }
     }
 
+    /// Returns the center of the bounding box.
+    pub fn center(&self) -> Point {
+        Point::new(
+            0.5 * (self.x.min() + self.x.max()),
+            0.5 * (self.y.min() + self.y.max()),
+            0.5 * (self.z.min() + self.z.max()),
+        )
+    }
+
+    /// Returns the vector from the smallest to the largest corner of the
+    /// bounding box.
+    pub fn diagonal(&self) -> Vec3 {
+        Vec3::new(self.x.size(), self.y.size(), self.z.size())
+    }
+
+    /// Determine whether the point `p` lies inside of the bounding box.
+    pub fn contains(&self, p: Point) -> bool {
+        self.x.contains(p.x()) && self.y.contains(p.y()) && self.z.contains(p.z())
+    }
+
     /// Determine whether `ray` hits the bounding box in interval `ray_t`.
     /// If so, returns a new interval where the ray and the box intersect.
     pub fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<Interval> {
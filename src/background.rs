@@ -0,0 +1,139 @@
+//! This module defines a trait for [Background]s, which give the color of the
+//! light that arrives along rays that leave the world without hitting
+//! anything. A [Color] is a background of a single color.
+
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{
+    color::Color, environment::equirectangular_uv, point::Point, texture::Texture, vec3::Vec3,
+};
+
+/// A trait for the background of a scene as a function of the direction of
+/// the rays that leave the world.
+///
+/// Implementing [Send] and [Sync] is required to concurrently render pixels.
+pub trait Background: Debug + Send + Sync {
+    /// The color of the background in `direction`.
+    fn color(&self, direction: Vec3) -> Color;
+}
+
+impl Background for Color {
+    fn color(&self, _direction: Vec3) -> Color {
+        *self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A background that blends linearly from one color straight down to another
+/// color straight up.
+///
+/// The sky of the first book is the gradient from white to light blue:
+///
+/// ```
+/// # use ray_tracing_weekend::{background::GradientBackground, color::Color};
+/// let sky = GradientBackground::new(Color::white(), Color::new(0.5, 0.7, 1.0));
+/// ```
+pub struct GradientBackground {
+    bottom: Color,
+    top: Color,
+}
+
+impl GradientBackground {
+    /// Create a new gradient from the color `bottom` to the color `top`.
+    pub fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+}
+
+impl Background for GradientBackground {
+    fn color(&self, direction: Vec3) -> Color {
+        let a = 0.5 * (direction.unit().y() + 1.0);
+        (1.0 - a) * self.bottom + a * self.top
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A background that maps a texture onto a dome around the world. Image
+/// textures of equirectangular panoramas are mapped like an
+/// [EnvironmentMap](crate::environment::EnvironmentMap), i.e., the top of the
+/// image is straight up, and so is the texture coordinate `v = 1` of other
+/// textures. The texture is evaluated at the texture coordinates and the unit
+/// direction as the point.
+pub struct TexturedBackground {
+    texture: Arc<dyn Texture>,
+}
+
+impl TexturedBackground {
+    /// Create a new background from a texture, e.g., an
+    /// [ImageTexture](crate::texture::ImageTexture) of an equirectangular
+    /// panorama.
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self { texture }
+    }
+}
+
+impl Background for TexturedBackground {
+    fn color(&self, direction: Vec3) -> Color {
+        // Texture coordinates grow upwards, but the polar angle downwards.
+        let p = Point::from(*direction.unit());
+        let (u, v) = equirectangular_uv(direction);
+        self.texture.value(u, 1.0 - v, p)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use image::{Rgb, Rgb32FImage};
+
+    use crate::{
+        color::Color,
+        environment::{equirectangular_direction, EnvironmentMap},
+        point::Point,
+        texture::Texture,
+        vec3::Vec3,
+        PI,
+    };
+
+    use super::{Background, GradientBackground, TexturedBackground};
+
+    #[derive(Debug)]
+    /// A texture whose color is the pixel of a `8 x 4` image at the texture
+    /// coordinates.
+    struct PixelTexture;
+
+    impl Texture for PixelTexture {
+        fn value(&self, u: f32, v: f32, _p: Point) -> Color {
+            Color::new((u * 8.0).floor(), ((1.0 - v) * 4.0).floor(), 0.0)
+        }
+    }
+
+    #[test]
+    fn gradient_blends_from_bottom_to_top() {
+        let gradient = GradientBackground::new(Color::white(), Color::new(0.5, 0.7, 1.0));
+        let up = gradient.color(Vec3::new(0.0, 2.0, 0.0));
+        let down = gradient.color(Vec3::new(0.0, -1.0, 0.0));
+        let horizon = gradient.color(Vec3::new(1.0, 0.0, 0.0));
+        assert!((up.r() - 0.5).abs() < 1e-6 && (up.g() - 0.7).abs() < 1e-6);
+        assert!((down.r() - 1.0).abs() < 1e-6 && (down.b() - 1.0).abs() < 1e-6);
+        assert!((horizon.r() - 0.75).abs() < 1e-6 && (horizon.g() - 0.85).abs() < 1e-6);
+    }
+
+    #[test]
+    fn textures_are_mapped_like_environment_maps() {
+        let image = Rgb32FImage::from_fn(8, 4, |x, y| Rgb([x as f32, y as f32, 0.0]));
+        let environment = EnvironmentMap::from_image(image);
+        let textured = TexturedBackground::new(Arc::new(PixelTexture));
+        for row in 0..4 {
+            for column in 0..8 {
+                let phi = 2.0 * PI * (column as f32 + 0.5) / 8.0;
+                let theta = PI * (row as f32 + 0.5) / 4.0;
+                let direction = equirectangular_direction(phi, theta);
+                for color in [environment.color(direction), textured.color(direction)] {
+                    assert_eq!((color.r(), color.g()), (column as f32, row as f32));
+                }
+            }
+        }
+    }
+}
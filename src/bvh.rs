@@ -2,7 +2,11 @@
 
 use std::sync::Arc;
 
-use crate::{aabb::AABB, hittable::Hittable, interval::Interval};
+use crate::{
+    aabb::AABB,
+    hittable::{hit_opaque, Hittable},
+    interval::Interval,
+};
 
 #[derive(Clone, Debug)]
 /// A BVH node with its bounding box, and left and right children.
@@ -72,14 +76,14 @@ impl Hittable for BVHNode {
     fn hit(&self, ray: &crate::ray::Ray, ray_t: Interval) -> Option<crate::hittable::HitRecord> {
         let hit = self.bounding_box.hit(ray, ray_t);
         hit.map(|ray_t| {
-            let hit_left = self.left.hit(ray, ray_t);
+            let hit_left = hit_opaque(self.left.as_ref(), ray, ray_t);
             let t1 = if let Some(rec) = &hit_left {
                 rec.t()
             } else {
                 ray_t.max()
             };
             let interval = Interval::new(ray_t.min(), t1);
-            let hit_right = self.right.hit(ray, interval);
+            let hit_right = hit_opaque(self.right.as_ref(), ray, interval);
             hit_right.or(hit_left)
         })?
     }
@@ -1,26 +1,29 @@
 //! This module contains the camera code which renders the image.
 //! See [Camera] for the implementation of the camera, see [CameraBuilder]
 //! for creating cameras.
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc};
 
 use image::{ImageBuffer, Rgb};
 use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
 use rayon::iter::ParallelIterator;
 
 use crate::{
+    background::Background,
     color::Color,
     degrees_to_radians,
-    hittable::{Hittable, World},
+    hittable::{HitRecord, Hittable, World},
     interval::Interval,
+    light::Light,
     point::Point,
     random_0_1_f32, random_in_unit_disk,
     ray::Ray,
+    spectrum::{wavelength_to_rgb, SampledSpectrum, SampledWavelengths},
     vec3::Vec3,
     INFINITY,
 };
 
 /// A camera that views the world.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct Camera {
     /// The width of the image we want to render.
     image_width: u32,
@@ -33,8 +36,8 @@ pub struct Camera {
     /// The maximum amount of times the traycing of a [Ray] can recurse. I.e.,
     /// how often a [Ray] can be scattered inside of the world.
     max_depth: u32,
-    /// The background color of the scene.
-    background: Color,
+    /// The background of the scene.
+    background: Arc<dyn Background>,
     /// The center of the camera.
     center: Point,
     /// The location of the pixel with coordinates (0, 0) in the world.
@@ -51,6 +54,8 @@ pub struct Camera {
     defocus_disk_v: Vec3,
     /// Toggle to hide the progress bar.
     hide_progress: bool,
+    /// Toggle to trace wavelengths of light instead of RGB colors.
+    spectral: bool,
 }
 
 impl Camera {
@@ -67,7 +72,8 @@ impl Camera {
         defocus_angle: f32,
         focus_distance: f32,
         hide_progress: bool,
-        background: Color,
+        background: Arc<dyn Background>,
+        spectral: bool,
     ) -> Self {
         // Calculate image height
         let image_height: u32 = (image_width as f32 / aspect_ratio) as u32;
@@ -119,6 +125,7 @@ impl Camera {
             defocus_angle,
             hide_progress,
             background,
+            spectral,
         }
     }
 
@@ -162,7 +169,14 @@ impl Camera {
         let mut pixel_color = Color::black();
         for _ in 0..self.samples_per_pixel {
             let ray = self.get_ray(x, y);
-            pixel_color += self.ray_color(&ray, self.max_depth, world);
+            pixel_color += if self.spectral {
+                let mut wavelengths = SampledWavelengths::sample_visible(random_0_1_f32());
+                let ray = ray.with_wavelength(Some(wavelengths.hero()));
+                self.spectral_ray_color(&ray, self.max_depth, 0.0, world, &mut wavelengths)
+                    .to_rgb(&wavelengths)
+            } else {
+                self.ray_color(&ray, self.max_depth, 0.0, world)
+            };
         }
         pixel_color
     }
@@ -193,30 +207,180 @@ impl Camera {
         self.center + p.x() * self.defocus_disk_u + p.y() * self.defocus_disk_v
     }
 
-    fn ray_color(&self, ray: &Ray, depth: u32, world: &World) -> Color {
+    /// Compute the light arriving along `ray`, which was scattered into its
+    /// direction with the density `scatter_pdf`, see
+    /// [Material::pdf](crate::material::Material::pdf).
+    fn ray_color(&self, ray: &Ray, depth: u32, scatter_pdf: f32, world: &World) -> Color {
         if depth == 0 {
             return Color::black();
         }
         let interval = Interval::new(0.001, INFINITY);
         let Some(hit_record) = world.hit(ray, interval) else {
-            return self.background;
+            return self.background(ray, scatter_pdf, world);
         };
 
-        let color_from_emission =
-            hit_record
-                .material()
-                .emitted(hit_record.u(), hit_record.v(), hit_record.p());
+        let color_from_emission = Self::emitted(ray, &hit_record, scatter_pdf, world)
+            + Self::direct_light(ray, &hit_record, world);
+        let Some((scattered, mut attenuation)) =
+            hit_record.material().scatter(ray, hit_record.copy())
+        else {
+            return color_from_emission;
+        };
+        // A material that depends on the wavelength, e.g., a dispersive
+        // dielectric, chose a wavelength for the path. From now on, the path
+        // only carries light of this wavelength, which is converted to RGB.
+        if let (None, Some(wavelength)) = (ray.wavelength(), scattered.wavelength()) {
+            attenuation = attenuation * wavelength_to_rgb(wavelength);
+        }
+        let scatter_pdf = hit_record
+            .material()
+            .pdf(ray, &hit_record, *scattered.direction());
+        let color_from_scatter =
+            attenuation * self.ray_color(&scattered, depth - 1, scatter_pdf, world);
+        color_from_scatter + color_from_emission
+    }
+
+    /// Compute the light emitted at the hit towards the origin of `ray`,
+    /// which was scattered with the density `scatter_pdf`. Lights of the
+    /// world are weighed against their explicit samples at the origin, see
+    /// [Camera::direct_light].
+    fn emitted(ray: &Ray, hit_record: &HitRecord, scatter_pdf: f32, world: &World) -> Color {
+        let emitted = hit_record.material().emitted(ray, hit_record);
+        if scatter_pdf <= 0.0 || emitted.luminance() <= 0.0 {
+            return emitted;
+        }
+        let light_pdf = world
+            .light_bvh()
+            .pdf(*ray.origin(), *ray.direction(), hit_record.p());
+        emitted * (scatter_pdf / (light_pdf + scatter_pdf))
+    }
+
+    /// Compute the light of the lights of `world` that is scattered towards
+    /// the origin of `ray` at the hit. Lights that are infinitely far away
+    /// are sampled at every hit, of the other lights one is picked by the
+    /// [LightBVH](crate::light_bvh::LightBVH) of the world. Shadow rays are
+    /// traced through the world, so that transparent parts of alpha-masked
+    /// objects let the light through.
+    fn direct_light(ray: &Ray, hit_record: &HitRecord, world: &World) -> Color {
+        let p = hit_record.p();
+        let material = hit_record.material();
+        let light_bvh = world.light_bvh();
+        let environment: Option<&dyn Light> = match world.environment() {
+            Some(environment) => Some(environment),
+            None => None,
+        };
+        let lights = light_bvh
+            .unbounded()
+            .iter()
+            .map(|light| (light.as_ref(), 1.0))
+            .chain(environment.map(|light| (light, 1.0)))
+            .chain(light_bvh.sample(p));
+
+        let mut result = Color::black();
+        for (light, probability) in lights {
+            let Some((direction, distance, radiance)) = light.sample(p) else {
+                continue;
+            };
+            // Scattered rays find lights with a density as well, e.g., the
+            // environment. Materials with an unknown density leave them to
+            // scattered rays, the others weigh both strategies by the balance
+            // heuristic.
+            let light_pdf = probability * light.pdf(p, direction);
+            let weight = if light_pdf > 0.0 {
+                let scatter_pdf = material.pdf(ray, hit_record, direction);
+                if scatter_pdf <= 0.0 {
+                    continue;
+                }
+                light_pdf / (light_pdf + scatter_pdf)
+            } else {
+                1.0
+            };
+            let f = material.eval(ray, hit_record, direction);
+            if f.luminance() <= 0.0 {
+                continue;
+            }
+            let shadow_ray = ray.spawn(p, direction);
+            let interval = Interval::new(0.001, distance - 0.001);
+            if world.hit(&shadow_ray, interval).is_none() {
+                result += f * radiance * (weight / probability);
+            }
+        }
+        result
+    }
+
+    /// Compute the light arriving along `ray`, which left the world. The
+    /// lights that are infinitely far away are weighed against their explicit
+    /// samples, see [Camera::direct_light].
+    fn background(&self, ray: &Ray, scatter_pdf: f32, world: &World) -> Color {
+        let direction = *ray.direction();
+        let weight = |light: &dyn Light| {
+            if scatter_pdf <= 0.0 {
+                return 1.0;
+            }
+            let light_pdf = light.pdf(*ray.origin(), direction);
+            scatter_pdf / (light_pdf + scatter_pdf)
+        };
+
+        let mut result = match world.environment() {
+            Some(environment) => environment.radiance(direction) * weight(environment),
+            None => self.background.color(direction),
+        };
+        for light in world.light_bvh().unbounded() {
+            let radiance = light.radiance(direction);
+            if radiance.luminance() > 0.0 {
+                result += radiance * weight(light.as_ref());
+            }
+        }
+        result
+    }
+
+    /// Compute the light arriving along `ray` at the sampled `wavelengths`.
+    /// The ray carries the hero wavelength. The RGB colors of the world are
+    /// upsampled to spectra.
+    fn spectral_ray_color(
+        &self,
+        ray: &Ray,
+        depth: u32,
+        scatter_pdf: f32,
+        world: &World,
+        wavelengths: &mut SampledWavelengths,
+    ) -> SampledSpectrum {
+        if depth == 0 {
+            return SampledSpectrum::constant(0.0);
+        }
+        let interval = Interval::new(0.001, INFINITY);
+        let Some(hit_record) = world.hit(ray, interval) else {
+            return SampledSpectrum::from_rgb(
+                self.background(ray, scatter_pdf, world),
+                wavelengths,
+            );
+        };
+
+        let color_from_emission = SampledSpectrum::from_rgb(
+            Self::emitted(ray, &hit_record, scatter_pdf, world)
+                + Self::direct_light(ray, &hit_record, world),
+            wavelengths,
+        );
         let Some((scattered, attenuation)) = hit_record.material().scatter(ray, hit_record.copy())
         else {
             return color_from_emission;
         };
-        let color_from_scatter = attenuation * self.ray_color(&scattered, depth - 1, world);
+        // The scattered direction is only valid for the hero wavelength.
+        if hit_record.material().is_dispersive() {
+            wavelengths.terminate_secondary();
+        }
+        let attenuation = SampledSpectrum::from_rgb(attenuation, wavelengths);
+        let scatter_pdf = hit_record
+            .material()
+            .pdf(ray, &hit_record, *scattered.direction());
+        let color_from_scatter = attenuation
+            * self.spectral_ray_color(&scattered, depth - 1, scatter_pdf, world, wavelengths);
         color_from_scatter + color_from_emission
     }
 }
 
 /// A builder for [Camera].
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct CameraBuilder {
     /// The aspect ratio for the [Camera].
     aspect_ratio: f32,
@@ -241,8 +405,10 @@ pub struct CameraBuilder {
     focus_distance: f32,
     /// Toggle to hide the progress bar.
     hide_progress: bool,
-    /// The background color of the scene.
-    background: Color,
+    /// The background of the scene.
+    background: Arc<dyn Background>,
+    /// Toggle to trace wavelengths of light instead of RGB colors.
+    spectral: bool,
 }
 
 impl CameraBuilder {
@@ -252,7 +418,7 @@ impl CameraBuilder {
     }
 
     /// Build a [Camera] from this builder.
-    pub fn build(self) -> Camera {
+    pub fn build(&self) -> Camera {
         Camera::new(
             self.aspect_ratio,
             self.image_width,
@@ -265,7 +431,8 @@ impl CameraBuilder {
             self.defocus_angle,
             self.focus_distance,
             self.hide_progress,
-            self.background,
+            self.background.clone(),
+            self.spectral,
         )
     }
 
@@ -321,21 +488,34 @@ impl CameraBuilder {
     /// Customize defocus (also called depth of field) of the [Camera].
     ///
     /// * `defocus_angle` - The angle of the cone originating at the plane of
-    ///    perfect focus with apex at the camera center.
-    ///    A greater angle means a bigger radius of the defocus disc.
+    ///   perfect focus with apex at the camera center.
+    ///   A greater angle means a bigger radius of the defocus disc.
     /// * `focus_distance` - The distance from the camera center to the plane of
-    ///    perfect focus.
+    ///   perfect focus.
     pub fn with_defocus(&mut self, defocus_angle: f32, focus_distance: f32) -> &mut Self {
         self.defocus_angle = defocus_angle;
         self.focus_distance = focus_distance;
         self
     }
 
-    /// Set the background color of the scene.
+    /// Set the background of the scene, e.g., a single [Color] or a
+    /// [GradientBackground](crate::background::GradientBackground). An
+    /// environment map of the world replaces it, see [World::set_environment].
     ///
-    /// * `background` - The background color.
-    pub fn background(&mut self, background: Color) -> &mut Self {
-        self.background = background;
+    /// * `background` - The background.
+    pub fn background(&mut self, background: impl Background + 'static) -> &mut Self {
+        self.background = Arc::new(background);
+        self
+    }
+
+    /// Toggle spectral rendering. In spectral mode, each sample traces
+    /// several wavelengths of light instead of RGB colors. The wavelengths
+    /// share a path until it hits a dispersive material, which makes
+    /// dispersion converge faster than with single wavelengths.
+    /// The RGB colors of the scene are upsampled to spectra, and the image is
+    /// converted from CIE XYZ to sRGB.
+    pub fn spectral(&mut self, spectral: bool) -> &mut Self {
+        self.spectral = spectral;
         self
     }
 }
@@ -354,7 +534,8 @@ impl Default for CameraBuilder {
             defocus_angle: 0.0,
             focus_distance: 10.0,
             hide_progress: false,
-            background: Color::new(0.70, 0.80, 1.00),
+            background: Arc::new(Color::new(0.70, 0.80, 1.00)),
+            spectral: false,
         }
     }
 }
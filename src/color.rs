@@ -31,6 +31,30 @@ impl Color {
         Color(Vec3::new(1.0, 1.0, 1.0))
     }
 
+    #[inline]
+    /// The red component of the color.
+    pub fn r(&self) -> f32 {
+        self.0.x()
+    }
+
+    #[inline]
+    /// The green component of the color.
+    pub fn g(&self) -> f32 {
+        self.0.y()
+    }
+
+    #[inline]
+    /// The blue component of the color.
+    pub fn b(&self) -> f32 {
+        self.0.z()
+    }
+
+    #[inline]
+    /// The luminance of the color, i.e., how bright it is perceived.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.0.x() + 0.7152 * self.0.y() + 0.0722 * self.0.z()
+    }
+
     /// Extracts the rgb values from the color.
     pub fn rgb(&self) -> (u8, u8, u8) {
         let r = Self::linear_to_gamma(self.0.x());
@@ -0,0 +1,209 @@
+//! This module contains the code for constructive solid geometry (CSG). I.e.,
+//! hittables which are the union, intersection, or difference of two closed
+//! hittables.
+
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    hittable::{HitRecord, Hittable, Span},
+    interval::Interval,
+    ray::Ray,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// The boolean operation a [Csg] node applies to its two operands.
+pub enum CsgOperation {
+    /// The points which are inside of either operand.
+    Union,
+    /// The points which are inside of both operands.
+    Intersection,
+    /// The points which are inside of the left but not the right operand.
+    Difference,
+}
+
+impl CsgOperation {
+    /// Returns true iff a point that is inside of the left operand iff
+    /// `in_left` holds and inside of the right operand iff `in_right` holds
+    /// is inside of the result of the operation.
+    fn contains(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A hittable which combines two closed hittables with a [CsgOperation].
+/// The operands are combined by computing the [Span]s along a ray during which
+/// the ray is inside of each operand. Thus, both operands need to be closed.
+/// Each surface keeps its own material. Surfaces of the right operand which
+/// bound the result of a difference are turned inside out.
+pub struct Csg {
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+    operation: CsgOperation,
+    bounding_box: AABB,
+}
+
+impl Csg {
+    /// Combine `left` and `right` with the given `operation`.
+    pub fn new(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>, operation: CsgOperation) -> Self {
+        let bounding_box = match operation {
+            CsgOperation::Union => AABB::from_aabbs(left.bounding_box(), right.bounding_box()),
+            CsgOperation::Intersection => {
+                AABB::from_overlap(left.bounding_box(), right.bounding_box())
+            }
+            CsgOperation::Difference => *left.bounding_box(),
+        };
+        Self {
+            left,
+            right,
+            operation,
+            bounding_box,
+        }
+    }
+
+    /// Create the union of `left` and `right`.
+    pub fn union(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOperation::Union)
+    }
+
+    /// Create the intersection of `left` and `right`.
+    pub fn intersection(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOperation::Intersection)
+    }
+
+    /// Create the difference of `left` minus `right`.
+    pub fn difference(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOperation::Difference)
+    }
+
+    /// Combine the spans of the left and right operands by sweeping over the
+    /// points where the ray enters or exits either of them.
+    fn combine(&self, left: Vec<Span>, right: Vec<Span>) -> Vec<Span> {
+        // Each event is the record of the boundary, whether it belongs to the
+        // left operand, and whether the ray enters the operand at this boundary.
+        let mut events: Vec<(HitRecord, bool, bool)> = Vec::new();
+        for (spans, is_left) in [(left, true), (right, false)] {
+            for span in spans {
+                let (enter, exit) = span.into_records();
+                events.push((enter, is_left, true));
+                events.push((exit, is_left, false));
+            }
+        }
+        events.sort_by(|a, b| a.0.t().total_cmp(&b.0.t()));
+
+        let mut spans = Vec::new();
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut enter: Option<HitRecord> = None;
+        for (mut record, is_left, entering) in events {
+            let was_inside = self.operation.contains(in_left, in_right);
+            if is_left {
+                in_left = entering;
+            } else {
+                in_right = entering;
+            }
+            let is_inside = self.operation.contains(in_left, in_right);
+            if was_inside == is_inside {
+                continue;
+            }
+            // The ray enters the result iff it is inside after this event.
+            // Records whose face disagrees with that belong to surfaces which
+            // are turned inside out by the operation.
+            if record.front_face() != is_inside {
+                record.flip();
+            }
+            if is_inside {
+                enter = Some(record);
+            } else if let Some(enter) = enter.take() {
+                spans.push(Span::new(enter, record));
+            }
+        }
+        spans
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.bounding_box.hit(ray, ray_t)?;
+        self.spans(ray)
+            .into_iter()
+            .flat_map(|span| {
+                let (enter, exit) = span.into_records();
+                [enter, exit]
+            })
+            .find(|record| ray_t.surrounds(record.t()))
+    }
+
+    fn bounding_box(&self) -> &AABB {
+        &self.bounding_box
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        if self.bounding_box.hit(ray, Interval::universe()).is_none() {
+            return Vec::new();
+        }
+        let left = self.left.spans(ray);
+        let right = self.right.spans(ray);
+        self.combine(left, right)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        color::Color, hittable::Hittable, hittable::Sphere, interval::Interval,
+        material::Lambertian, point::Point, ray::Ray, vec3::Vec3,
+    };
+
+    use super::Csg;
+
+    fn spheres() -> (Arc<Sphere>, Arc<Sphere>) {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let left = Sphere::new(Point::new(-0.5, 0.0, 0.0), 1.0, material.clone());
+        let right = Sphere::new(Point::new(0.5, 0.0, 0.0), 1.0, material);
+        (Arc::new(left), Arc::new(right))
+    }
+
+    #[test]
+    fn csg_spans() {
+        let (left, right) = spheres();
+        let ray = Ray::new(Point::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+
+        let union = Csg::union(left.clone(), right.clone()).spans(&ray);
+        assert_eq!(union.len(), 1);
+        assert!((union[0].enter().t() - 3.5).abs() < 1e-4);
+        assert!((union[0].exit().t() - 6.5).abs() < 1e-4);
+
+        let intersection = Csg::intersection(left.clone(), right.clone()).spans(&ray);
+        assert_eq!(intersection.len(), 1);
+        assert!((intersection[0].enter().t() - 4.5).abs() < 1e-4);
+        assert!((intersection[0].exit().t() - 5.5).abs() < 1e-4);
+
+        let difference = Csg::difference(left, right).spans(&ray);
+        assert_eq!(difference.len(), 1);
+        assert!((difference[0].enter().t() - 3.5).abs() < 1e-4);
+        assert!((difference[0].exit().t() - 4.5).abs() < 1e-4);
+        assert!(!difference[0].exit().front_face());
+    }
+
+    #[test]
+    fn hit_difference_from_inside_the_hole() {
+        let (left, right) = spheres();
+        let difference = Csg::difference(left, right);
+        // Start inside of the carved out region, which is outside of the result.
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let hit = difference
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .expect("The ray hits the carved out surface.");
+        assert!((hit.t() - 0.5).abs() < 1e-4);
+        assert!(hit.front_face());
+        assert!(hit.normal().x() > 0.0);
+    }
+}
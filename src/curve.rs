@@ -0,0 +1,293 @@
+//! This module contains the code for curve hittables, which are thin cubic
+//! Bézier curves with a varying width. They are meant to model hair, fur, and
+//! grass, i.e., very many small objects which are best put into a BVH.
+
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::Material,
+    onb::ONB,
+    point::Point,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+/// The maximum number of times a curve is split in half during intersection.
+const MAX_DEPTH: i32 = 10;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How the surface of a [Curve] is shaded.
+pub enum CurveType {
+    /// A flat ribbon that always faces the incoming ray.
+    Flat,
+    /// A ribbon whose normals are bent as if it were a cylinder.
+    Cylinder,
+}
+
+#[derive(Clone, Debug)]
+/// A cubic Bézier curve whose width varies linearly along its length. The
+/// curve is intersected as a ribbon that faces the ray, which is a good
+/// approximation for curves that are thin compared to the image resolution.
+pub struct Curve {
+    /// The four control points of the Bézier curve.
+    control_points: [Point; 4],
+    /// The width at the start of the curve.
+    width0: f32,
+    /// The width at the end of the curve.
+    width1: f32,
+    curve_type: CurveType,
+    material: Arc<dyn Material>,
+    bounding_box: AABB,
+}
+
+impl Curve {
+    /// Create a new curve.
+    ///
+    /// * `control_points` - The control points of the cubic Bézier curve. The
+    ///   curve starts at the first and ends at the last point.
+    /// * `width0` - The width at the start of the curve.
+    /// * `width1` - The width at the end of the curve.
+    /// * `curve_type` - How the surface of the curve is shaded.
+    /// * `material` - The surface material of the curve.
+    pub fn new(
+        control_points: [Point; 4],
+        width0: f32,
+        width1: f32,
+        curve_type: CurveType,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let radius = 0.5 * width0.max(width1);
+        let padding = Vec3::new(radius, radius, radius);
+        let bounding_box = control_points
+            .iter()
+            .fold(AABB::empty(), |bounding_box, &p| {
+                AABB::from_aabbs(&bounding_box, &AABB::from_points(p - padding, p + padding))
+            });
+        Self {
+            control_points,
+            width0,
+            width1,
+            curve_type,
+            material,
+            bounding_box,
+        }
+    }
+
+    /// Recursively intersect the curve segment from `u0` to `u1` whose
+    /// control points are given in ray space. Ray space has the ray origin at
+    /// its origin and the ray direction along its z-axis. Returns the distance
+    /// along the ray and the texture coordinates of the closest hit in `z_range`.
+    fn recursive_hit(
+        &self,
+        control_points: [Vec3; 4],
+        u0: f32,
+        u1: f32,
+        z_range: Interval,
+        depth: i32,
+    ) -> Option<(f32, f32, f32)> {
+        // Cull segments whose bounds do not contain the ray.
+        let max_width =
+            0.5 * lerp(u0, self.width0, self.width1).max(lerp(u1, self.width0, self.width1));
+        let (min, max) = bounds(&control_points);
+        if max.x() + max_width < 0.0
+            || min.x() - max_width > 0.0
+            || max.y() + max_width < 0.0
+            || min.y() - max_width > 0.0
+            || max.z() + max_width < z_range.min()
+            || min.z() - max_width > z_range.max()
+        {
+            return None;
+        }
+
+        if depth > 0 {
+            let (left, right) = split_bezier(&control_points);
+            let u_mid = 0.5 * (u0 + u1);
+            let left_hit = self.recursive_hit(left, u0, u_mid, z_range, depth - 1);
+            let z_max = left_hit.map_or(z_range.max(), |(z, _, _)| z);
+            let z_range = Interval::new(z_range.min(), z_max);
+            return self
+                .recursive_hit(right, u_mid, u1, z_range, depth - 1)
+                .or(left_hit);
+        }
+
+        // Treat the segment as a line and check that the ray passes between
+        // the planes at its start and end that are perpendicular to it.
+        let [p0, p1, p2, p3] = control_points;
+        let edge = (p1.y() - p0.y()) * -p0.y() + p0.x() * (p0.x() - p1.x());
+        if edge < 0.0 {
+            return None;
+        }
+        let edge = (p2.y() - p3.y()) * -p3.y() + p3.x() * (p3.x() - p2.x());
+        if edge < 0.0 {
+            return None;
+        }
+
+        // Find the closest point on the segment to the ray and check whether
+        // the ray is within the width of the curve there.
+        let segment = Vec3::new(p3.x() - p0.x(), p3.y() - p0.y(), 0.0);
+        let denom = segment.length_squared();
+        if denom == 0.0 {
+            return None;
+        }
+        let w = (Vec3::new(-p0.x(), -p0.y(), 0.0).dot(segment) / denom).clamp(0.0, 1.0);
+        let u = lerp(w, u0, u1);
+        let hit_width = lerp(u, self.width0, self.width1);
+        let (pc, tangent) = eval_bezier(&control_points, w);
+        let distance_squared = pc.x() * pc.x() + pc.y() * pc.y();
+        if distance_squared > 0.25 * hit_width * hit_width || !z_range.surrounds(pc.z()) {
+            return None;
+        }
+
+        // The offset across the curve from one edge (0) to the other (1).
+        let distance = distance_squared.sqrt();
+        let side = tangent.x() * -pc.y() + pc.x() * tangent.y();
+        let v = if side > 0.0 {
+            0.5 + distance / hit_width
+        } else {
+            0.5 - distance / hit_width
+        };
+        Some((pc.z(), u, v))
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let ray_t = self.bounding_box.hit(ray, ray_t)?;
+        let ray_length = ray.direction().length();
+        let frame = ONB::new(ray.direction().unit());
+        let control_points = self
+            .control_points
+            .map(|p| frame.to_local(p - *ray.origin()));
+
+        // Choose the number of subdivisions such that the segments are close
+        // to straight lines compared to the width of the curve.
+        let [p0, p1, p2, p3] = control_points;
+        let l0 = (p0 - 2.0 * p1 + p2)
+            .length()
+            .max((p1 - 2.0 * p2 + p3).length());
+        let epsilon = 0.05 * self.width0.max(self.width1);
+        let depth = if epsilon > 0.0 && l0 > 0.0 {
+            ((std::f32::consts::SQRT_2 * 6.0 * l0 / (8.0 * epsilon)).log2() / 2.0).round() as i32
+        } else {
+            0
+        };
+
+        let z_range = Interval::new(ray_t.min() * ray_length, ray_t.max() * ray_length);
+        let (z, u, v) =
+            self.recursive_hit(control_points, 0.0, 1.0, z_range, depth.clamp(0, MAX_DEPTH))?;
+        let t = z / ray_length;
+
+        // Compute the normal in world space. It faces the ray and is bent
+        // across the curve for cylinders.
+        let (_, tangent) = eval_bezier(&self.control_points.map(|p| *p), u);
+        let direction = *ray.direction().unit();
+        let facing = -(direction - direction.dot(*tangent.unit()) * *tangent.unit());
+        let normal = match self.curve_type {
+            CurveType::Flat => facing.unit(),
+            CurveType::Cylinder => {
+                let across = facing.cross(tangent).unit();
+                let offset = 2.0 * v - 1.0;
+                (offset * *across + (1.0 - offset * offset).max(0.0).sqrt() * *facing.unit()).unit()
+            }
+        };
+
+        let mut hit_record = HitRecord::new(ray, ray.at(t), normal, t, u, v, self.material.clone());
+        hit_record.set_tangent(tangent);
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &AABB {
+        &self.bounding_box
+    }
+}
+
+#[inline]
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    (1.0 - t) * a + t * b
+}
+
+/// Compute the component-wise minimum and maximum of the control points.
+fn bounds(control_points: &[Vec3; 4]) -> (Vec3, Vec3) {
+    control_points
+        .iter()
+        .skip(1)
+        .fold((control_points[0], control_points[0]), |(min, max), p| {
+            (
+                Vec3::new(min.x().min(p.x()), min.y().min(p.y()), min.z().min(p.z())),
+                Vec3::new(max.x().max(p.x()), max.y().max(p.y()), max.z().max(p.z())),
+            )
+        })
+}
+
+/// Split a cubic Bézier curve at its middle into two curves.
+fn split_bezier(control_points: &[Vec3; 4]) -> ([Vec3; 4], [Vec3; 4]) {
+    let [p0, p1, p2, p3] = *control_points;
+    let p01 = 0.5 * (p0 + p1);
+    let p12 = 0.5 * (p1 + p2);
+    let p23 = 0.5 * (p2 + p3);
+    let p012 = 0.5 * (p01 + p12);
+    let p123 = 0.5 * (p12 + p23);
+    let mid = 0.5 * (p012 + p123);
+    ([p0, p01, p012, mid], [mid, p123, p23, p3])
+}
+
+/// Evaluate a cubic Bézier curve and its derivative at `t`.
+fn eval_bezier(control_points: &[Vec3; 4], t: f32) -> (Vec3, Vec3) {
+    let [p0, p1, p2, p3] = *control_points;
+    let lerp3 = |a: Vec3, b: Vec3| (1.0 - t) * a + t * b;
+    let cp1 = [lerp3(p0, p1), lerp3(p1, p2), lerp3(p2, p3)];
+    let cp2 = [lerp3(cp1[0], cp1[1]), lerp3(cp1[1], cp1[2])];
+    let derivative = if (cp2[1] - cp2[0]).length_squared() > 0.0 {
+        3.0 * (cp2[1] - cp2[0])
+    } else {
+        // The derivative vanishes at degenerate control points.
+        p3 - p0
+    };
+    (lerp3(cp2[0], cp2[1]), derivative)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        color::Color, hittable::Hittable, interval::Interval, material::Lambertian, point::Point,
+        ray::Ray, vec3::Vec3,
+    };
+
+    use super::{Curve, CurveType};
+
+    #[test]
+    fn hit_curve() {
+        // A straight curve along the x-axis that narrows from 0.2 to 0.1.
+        let curve = Curve::new(
+            [
+                Point::new(-1.0, 0.0, 0.0),
+                Point::new(-0.5, 0.0, 0.0),
+                Point::new(0.5, 0.0, 0.0),
+                Point::new(1.0, 0.0, 0.0),
+            ],
+            0.2,
+            0.1,
+            CurveType::Cylinder,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = curve
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .expect("The ray hits the curve.");
+        assert!((hit.t() - 5.0).abs() < 1e-3);
+        assert!((hit.u() - 0.5).abs() < 1e-3);
+        assert!((hit.normal().z() - 1.0).abs() < 1e-3);
+        assert!((hit.tangent().x() - 1.0).abs() < 1e-3);
+
+        // The curve is thinner than 0.2 at its end.
+        let ray = Ray::new(Point::new(0.9, 0.08, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(curve
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .is_none());
+    }
+}
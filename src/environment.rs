@@ -0,0 +1,260 @@
+//! This module contains the code for lighting a scene by an environment map,
+//! i.e., an equirectangular image of the light that arrives from every
+//! direction, like a photographed sky. See [EnvironmentMap].
+
+use std::path::Path;
+
+use image::Rgb32FImage;
+
+use crate::{
+    background::Background, color::Color, degrees_to_radians, light::Light, point::Point,
+    random_0_1_f32, vec3::Vec3, INFINITY, PI,
+};
+
+/// The unit direction at the azimuth `phi` and the polar angle `theta` from
+/// straight up, both in radians, in the mapping of an [EnvironmentMap]
+/// without rotation.
+pub(crate) fn equirectangular_direction(phi: f32, theta: f32) -> Vec3 {
+    let (sin_theta, cos_theta) = theta.sin_cos();
+    Vec3::new(-sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin())
+}
+
+/// The inverse of [equirectangular_direction] scaled to `[0, 1]`, i.e., the
+/// azimuth `u` and the polar angle `v` from straight up of `direction` as
+/// fractions of the width and the height of an [EnvironmentMap].
+pub(crate) fn equirectangular_uv(direction: Vec3) -> (f32, f32) {
+    let d = direction.unit();
+    let u = (f32::atan2(-d.z(), d.x()) + PI) / (2.0 * PI);
+    let v = f32::acos(d.y().clamp(-1.0, 1.0)) / PI;
+    (u, v)
+}
+
+#[derive(Debug, Clone)]
+/// A discrete distribution over the indices of a slice of weights.
+struct Distribution1D {
+    /// The cumulative distribution, which starts at 0 and ends at 1.
+    cdf: Vec<f32>,
+}
+
+impl Distribution1D {
+    /// Create a distribution whose probabilities are proportional to
+    /// `weights`. If all weights are zero, the distribution is uniform.
+    fn new(weights: &[f32]) -> Self {
+        let total: f64 = weights.iter().map(|&weight| weight as f64).sum();
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        let mut sum = 0.0;
+        cdf.push(0.0);
+        for (i, &weight) in weights.iter().enumerate() {
+            sum += weight as f64;
+            let value = if total > 0.0 {
+                sum / total
+            } else {
+                (i + 1) as f64 / weights.len() as f64
+            };
+            cdf.push(value as f32);
+        }
+        Self { cdf }
+    }
+
+    /// Sample an index from a uniform random number `u` in `[0, 1)`.
+    fn sample(&self, u: f32) -> usize {
+        let index = self.cdf.partition_point(|&c| c <= u);
+        index.clamp(1, self.cdf.len() - 1) - 1
+    }
+
+    /// The probability of sampling `index`.
+    fn probability(&self, index: usize) -> f32 {
+        self.cdf[index + 1] - self.cdf[index]
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Light that arrives from infinitely far away, given by an equirectangular
+/// HDR image. It is seen by rays that leave the scene, and it is sampled
+/// explicitly as a [Light] proportional to its luminance, such that small,
+/// bright regions like the sun are found quickly.
+///
+/// The image uses the same mapping as the texture coordinates of a
+/// [Sphere](crate::hittable::Sphere), i.e., the top row of the image is
+/// straight up.
+pub struct EnvironmentMap {
+    image: Rgb32FImage,
+    /// The sine and cosine of the rotation around the y-axis.
+    sin_rotation: f32,
+    cos_rotation: f32,
+    /// The distribution of the rows of the image.
+    rows: Distribution1D,
+    /// The distributions of the pixels in each row.
+    columns: Vec<Distribution1D>,
+}
+
+impl EnvironmentMap {
+    /// Loads an environment map from an HDR image, e.g., a `.hdr` or `.exr`
+    /// file. Panics if the loading fails.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self::from_image(
+            image::open(path)
+                .expect("Failed to load image.")
+                .into_rgb32f(),
+        )
+    }
+
+    /// Create an environment map from an image in linear RGB.
+    pub fn from_image(image: Rgb32FImage) -> Self {
+        let (width, height) = image.dimensions();
+
+        // The pixels near the poles cover a smaller solid angle, so they are
+        // weighted by the sine of their polar angle.
+        let columns: Vec<_> = (0..height)
+            .map(|y| {
+                let sin_theta = (PI * (y as f32 + 0.5) / height as f32).sin();
+                let weights: Vec<_> = (0..width)
+                    .map(|x| {
+                        let pixel = image.get_pixel(x, y);
+                        let color = Color::new(pixel[0], pixel[1], pixel[2]);
+                        color.luminance().max(0.0) * sin_theta
+                    })
+                    .collect();
+                let row_weight = weights.iter().sum::<f32>();
+                (Distribution1D::new(&weights), row_weight)
+            })
+            .collect();
+        let row_weights: Vec<_> = columns.iter().map(|(_, weight)| *weight).collect();
+        Self {
+            image,
+            sin_rotation: 0.0,
+            cos_rotation: 1.0,
+            rows: Distribution1D::new(&row_weights),
+            columns: columns.into_iter().map(|(columns, _)| columns).collect(),
+        }
+    }
+
+    /// Rotate the environment by `angle` degrees around the y-axis.
+    pub fn with_rotation(mut self, angle: f32) -> Self {
+        let angle = degrees_to_radians(angle);
+        self.sin_rotation = angle.sin();
+        self.cos_rotation = angle.cos();
+        self
+    }
+
+    /// The pixel of the image that is seen in `direction`.
+    fn pixel(&self, direction: Vec3) -> (u32, u32) {
+        // Undo the rotation of the map.
+        let d = direction.unit();
+        let (u, v) = equirectangular_uv(Vec3::new(
+            self.cos_rotation * d.x() - self.sin_rotation * d.z(),
+            d.y(),
+            self.sin_rotation * d.x() + self.cos_rotation * d.z(),
+        ));
+        let (width, height) = self.image.dimensions();
+        let column = ((u * width as f32) as u32).min(width - 1);
+        let row = ((v * height as f32) as u32).min(height - 1);
+        (column, row)
+    }
+}
+
+impl Light for EnvironmentMap {
+    fn sample(&self, _p: Point) -> Option<(Vec3, f32, Color)> {
+        // Pick a pixel proportional to its weight and a point inside of it.
+        let row = self.rows.sample(random_0_1_f32());
+        let column = self.columns[row].sample(random_0_1_f32());
+        let (width, height) = self.image.dimensions();
+        let phi = 2.0 * PI * (column as f32 + random_0_1_f32()) / width as f32;
+        let theta = PI * (row as f32 + random_0_1_f32()) / height as f32;
+
+        // Invert the mapping of the pixels and apply the rotation.
+        let d = equirectangular_direction(phi, theta);
+        let direction = Vec3::new(
+            self.cos_rotation * d.x() + self.sin_rotation * d.z(),
+            d.y(),
+            -self.sin_rotation * d.x() + self.cos_rotation * d.z(),
+        );
+
+        let pdf = self.pdf(Point::new(0.0, 0.0, 0.0), direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some((direction, INFINITY, self.radiance(direction) * (1.0 / pdf)))
+    }
+
+    fn pdf(&self, _p: Point, direction: Vec3) -> f32 {
+        // The probability of the pixel is spread uniformly over its area in
+        // the image. Mapping it onto the sphere stretches an area of 1 by
+        // 2 * PI^2 * sin(theta) / (width * height).
+        let (column, row) = self.pixel(direction);
+        let (width, height) = self.image.dimensions();
+        let cos_theta = direction.unit().y();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        let probability = self.rows.probability(row as usize)
+            * self.columns[row as usize].probability(column as usize);
+        probability * (width * height) as f32 / (2.0 * PI * PI * sin_theta)
+    }
+
+    fn radiance(&self, direction: Vec3) -> Color {
+        let (column, row) = self.pixel(direction);
+        let pixel = self.image.get_pixel(column, row);
+        Color::new(pixel[0], pixel[1], pixel[2])
+    }
+}
+
+/// An environment map is also a background that is not sampled as a light,
+/// e.g., to show a panorama behind the scene that does not light it.
+impl Background for EnvironmentMap {
+    fn color(&self, direction: Vec3) -> Color {
+        self.radiance(direction)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use image::{Rgb, Rgb32FImage};
+
+    use crate::{light::Light, point::Point, vec3::Vec3, PI};
+
+    use super::EnvironmentMap;
+
+    #[test]
+    fn samples_follow_the_luminance() {
+        // A dim map with a single bright pixel, rotated by a quarter turn.
+        let mut image = Rgb32FImage::from_pixel(64, 32, Rgb([0.1, 0.1, 0.1]));
+        image.put_pixel(40, 10, Rgb([10000.0, 10000.0, 10000.0]));
+        let environment = EnvironmentMap::from_image(image).with_rotation(90.0);
+        let p = Point::new(0.0, 0.0, 0.0);
+
+        let mut bright = 0;
+        for _ in 0..1000 {
+            let (direction, _, radiance) = environment.sample(p).unwrap();
+            assert!((direction.length() - 1.0).abs() < 1e-4);
+            if environment.radiance(direction).r() > 1.0 {
+                bright += 1;
+            }
+            // The radiance divided by the pdf is consistent with the pdf of
+            // the sampled direction.
+            let pdf = environment.pdf(p, direction);
+            let expected = environment.radiance(direction).r() / pdf;
+            assert!((radiance.r() - expected).abs() <= 1e-3 * expected);
+        }
+        assert!(bright > 900);
+
+        // The pdf integrates to 1 over the sphere. The grid of the integral is
+        // aligned with the pixels.
+        let n = 256;
+        let mut integral = 0.0;
+        for i in 0..n {
+            let theta = PI * (i as f32 + 0.5) / n as f32;
+            for j in 0..2 * n {
+                let phi = PI * (j as f32 + 0.5) / n as f32;
+                let direction = Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                integral += environment.pdf(p, direction) * theta.sin() * (PI / n as f32).powi(2);
+            }
+        }
+        assert!((integral - 1.0).abs() < 0.05);
+    }
+}
@@ -0,0 +1,339 @@
+//! This module contains the code for heightfield hittables. I.e., terrain
+//! which is given by a grid of heights, e.g., from a grayscale heightmap.
+
+use std::{path::Path, sync::Arc};
+
+use crate::{
+    aabb::AABB,
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    material::Material,
+    point::Point,
+    ray::Ray,
+    vec3::{Unit3, Vec3},
+    INFINITY,
+};
+
+#[derive(Debug, Clone)]
+/// A terrain given by a regular grid of heights. Each cell of the grid is
+/// split into two triangles. The normals are interpolated across the
+/// triangles to shade the terrain smoothly, and the texture coordinates span
+/// the whole field.
+pub struct Heightfield {
+    /// The number of samples along the x-axis.
+    width: usize,
+    /// The number of samples along the z-axis.
+    depth: usize,
+    /// The heights of the samples in world space, row by row along the z-axis.
+    heights: Vec<f32>,
+    /// The normals at the samples, in the same order as `heights`.
+    normals: Vec<Unit3>,
+    /// The corner of the field with the smallest x and z coordinates.
+    corner: Point,
+    /// The extent of the field along the x and z axes.
+    size: Vec3,
+    /// The distance between two samples along the x-axis.
+    dx: f32,
+    /// The distance between two samples along the z-axis.
+    dz: f32,
+    material: Arc<dyn Material>,
+    bounding_box: AABB,
+}
+
+impl Heightfield {
+    /// Load a heightfield from a grayscale heightmap at the given path. Panics
+    /// if the loading fails. Each pixel is a sample of the field, where black
+    /// is height zero and white is the maximum height.
+    ///
+    /// * `corner` - The corner of the field with the smallest coordinates.
+    /// * `size` - The extent of the field along the x and z axes. The y
+    ///   component is the maximum height.
+    /// * `material` - The surface material of the field.
+    pub fn new(
+        path: impl AsRef<Path>,
+        corner: Point,
+        size: Vec3,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let image = image::open(path)
+            .expect("Failed to load heightmap.")
+            .into_luma16();
+        let heights = image
+            .pixels()
+            .map(|pixel| pixel[0] as f32 / u16::MAX as f32)
+            .collect();
+        Self::from_heights(
+            image.width() as usize,
+            image.height() as usize,
+            heights,
+            corner,
+            size,
+            material,
+        )
+    }
+
+    /// Create a heightfield from a grid of `width * depth` heights in `[0, 1]`,
+    /// given row by row along the z-axis. Panics if the grid has less than two
+    /// samples along an axis or if the number of heights does not match.
+    ///
+    /// * `corner` - The corner of the field with the smallest coordinates.
+    /// * `size` - The extent of the field along the x and z axes. The y
+    ///   component is the maximum height.
+    /// * `material` - The surface material of the field.
+    pub fn from_heights(
+        width: usize,
+        depth: usize,
+        heights: Vec<f32>,
+        corner: Point,
+        size: Vec3,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(width >= 2 && depth >= 2, "A heightfield needs 2x2 samples.");
+        assert_eq!(heights.len(), width * depth, "Wrong number of heights.");
+        let heights: Vec<f32> = heights.into_iter().map(|h| h * size.y()).collect();
+        let dx = size.x() / (width - 1) as f32;
+        let dz = size.z() / (depth - 1) as f32;
+
+        // Estimate the normals from the slope between the neighboring samples.
+        let height = |i: usize, j: usize| heights[j * width + i];
+        let mut normals = Vec::with_capacity(heights.len());
+        for j in 0..depth {
+            for i in 0..width {
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(width - 1));
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(depth - 1));
+                let slope_x = (height(i1, j) - height(i0, j)) / ((i1 - i0) as f32 * dx);
+                let slope_z = (height(i, j1) - height(i, j0)) / ((j1 - j0) as f32 * dz);
+                normals.push(Vec3::new(-slope_x, 1.0, -slope_z).unit());
+            }
+        }
+
+        let min_height = heights.iter().copied().fold(INFINITY, f32::min);
+        let max_height = heights.iter().copied().fold(-INFINITY, f32::max);
+        let bounding_box = AABB::from_points(
+            Point::new(corner.x(), corner.y() + min_height, corner.z()),
+            Point::new(
+                corner.x() + size.x(),
+                corner.y() + max_height,
+                corner.z() + size.z(),
+            ),
+        );
+
+        Self {
+            width,
+            depth,
+            heights,
+            normals,
+            corner,
+            size,
+            dx,
+            dz,
+            material,
+            bounding_box,
+        }
+    }
+
+    /// Return the world space position of the sample at `i` and `j`.
+    fn vertex(&self, i: usize, j: usize) -> Point {
+        Point::new(
+            self.corner.x() + i as f32 * self.dx,
+            self.corner.y() + self.heights[j * self.width + i],
+            self.corner.z() + j as f32 * self.dz,
+        )
+    }
+
+    /// Return the normal of the sample at `i` and `j`.
+    fn vertex_normal(&self, i: usize, j: usize) -> Vec3 {
+        *self.normals[j * self.width + i]
+    }
+
+    /// Intersect `ray` with the two triangles of the cell at `i` and `j`.
+    fn hit_cell(&self, ray: &Ray, ray_t: Interval, i: usize, j: usize) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let triangles = [
+            [corners[0], corners[1], corners[2]],
+            [corners[0], corners[2], corners[3]],
+        ];
+        // The `t`, the triangle, and the barycentric coordinates of the closest hit.
+        let mut closest = None;
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|(i, j)| self.vertex(i, j));
+            let max_t = closest.map_or(ray_t.max(), |(t, _, _, _)| t);
+            if let Some((t, beta, gamma)) =
+                hit_triangle(ray, Interval::new(ray_t.min(), max_t), a, b, c)
+            {
+                closest = Some((t, triangle, beta, gamma));
+            }
+        }
+
+        let (t, [a, b, c], beta, gamma) = closest?;
+        let p = ray.at(t);
+        let normal = (1.0 - beta - gamma) * self.vertex_normal(a.0, a.1)
+            + beta * self.vertex_normal(b.0, b.1)
+            + gamma * self.vertex_normal(c.0, c.1);
+        let u = (p.x() - self.corner.x()) / self.size.x();
+        let v = 1.0 - (p.z() - self.corner.z()) / self.size.z();
+        let mut hit_record = HitRecord::new(ray, p, normal.unit(), t, u, v, self.material.clone());
+
+        // The texture coordinate `u` grows along the x-axis and `v` against
+        // the z-axis, i.e., along the slopes of the triangle in the directions
+        // of these axes.
+        let [a, b, c] = [a, b, c].map(|(i, j)| self.vertex(i, j));
+        let face_normal = (b - a).cross(c - a);
+        hit_record.set_partials(
+            self.size.x() * Vec3::new(1.0, -face_normal.x() / face_normal.y(), 0.0),
+            self.size.z() * Vec3::new(0.0, face_normal.z() / face_normal.y(), -1.0),
+        );
+        Some(hit_record)
+    }
+}
+
+/// Intersect `ray` with the triangle `a`, `b`, `c` in `ray_t`. Returns the `t`
+/// of the intersection and the barycentric coordinates of `b` and `c`.
+fn hit_triangle(
+    ray: &Ray,
+    ray_t: Interval,
+    a: Point,
+    b: Point,
+    c: Point,
+) -> Option<(f32, f32, f32)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let pvec = ray.direction().cross(edge2);
+    let det = edge1.dot(pvec);
+    if det.abs() < 1e-8 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let tvec = *ray.origin() - a;
+    let beta = tvec.dot(pvec) * inv_det;
+    if !(0.0..=1.0).contains(&beta) {
+        return None;
+    }
+    let qvec = tvec.cross(edge1);
+    let gamma = ray.direction().dot(qvec) * inv_det;
+    if gamma < 0.0 || beta + gamma > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(qvec) * inv_det;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+    Some((t, beta, gamma))
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let box_t = self.bounding_box.hit(ray, ray_t)?;
+        let origin = ray.origin();
+        let direction = ray.direction();
+
+        // Walk through the cells of the grid along the ray, starting at the
+        // cell where the ray enters the bounding box.
+        let entry = ray.at(box_t.min());
+        let max_i = (self.width - 2) as i64;
+        let max_j = (self.depth - 2) as i64;
+        let mut i = (((entry.x() - self.corner.x()) / self.dx).floor() as i64).clamp(0, max_i);
+        let mut j = (((entry.z() - self.corner.z()) / self.dz).floor() as i64).clamp(0, max_j);
+
+        // The `t` at which the ray crosses the next cell boundary along an
+        // axis, how much `t` grows from one boundary to the next, and the
+        // direction in which the cell index moves.
+        let step = |index: i64, corner: f32, delta: f32, origin: f32, direction: f32| {
+            if direction > 0.0 {
+                let boundary = corner + (index + 1) as f32 * delta;
+                ((boundary - origin) / direction, delta / direction, 1)
+            } else if direction < 0.0 {
+                let boundary = corner + index as f32 * delta;
+                ((boundary - origin) / direction, -delta / direction, -1)
+            } else {
+                (INFINITY, INFINITY, 0)
+            }
+        };
+        let (mut next_x, delta_x, step_i) =
+            step(i, self.corner.x(), self.dx, origin.x(), direction.x());
+        let (mut next_z, delta_z, step_j) =
+            step(j, self.corner.z(), self.dz, origin.z(), direction.z());
+
+        loop {
+            let hit = self.hit_cell(ray, ray_t, i as usize, j as usize);
+            if hit.is_some() {
+                return hit;
+            }
+            if next_x < next_z {
+                if next_x > box_t.max() {
+                    return None;
+                }
+                i += step_i;
+                next_x += delta_x;
+            } else {
+                if next_z > box_t.max() {
+                    return None;
+                }
+                j += step_j;
+                next_z += delta_z;
+            }
+            if i < 0 || i > max_i || j < 0 || j > max_j {
+                return None;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> &AABB {
+        &self.bounding_box
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        color::Color, hittable::Hittable, interval::Interval, material::Lambertian, point::Point,
+        ray::Ray, vec3::Vec3,
+    };
+
+    use super::Heightfield;
+
+    #[test]
+    fn hit_slope() {
+        // A slope that rises along the x-axis from height 0 to height 1.
+        let heights = vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.0, 0.0, 0.5, 1.0];
+        let field = Heightfield::from_heights(
+            3,
+            3,
+            heights,
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 1.0, 2.0),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let ray = Ray::new(Point::new(1.5, 5.0, 0.5), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = field
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .expect("The ray hits the slope.");
+        assert!((hit.p().y() - 0.75).abs() < 1e-4);
+        assert!((hit.u() - 0.75).abs() < 1e-4);
+        assert!((hit.v() - 0.75).abs() < 1e-4);
+        assert!(hit.normal().x() < 0.0 && hit.normal().y() > 0.0);
+
+        // A ray inside of the bounding box that travels along the slope, just
+        // above it, crosses all cells without hitting it.
+        let ray = Ray::new(Point::new(0.01, 0.055, 0.5), Vec3::new(1.0, 0.5, 0.0), 0.0);
+        assert!(field
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .is_none());
+
+        // A level ray that starts above the first cell hits the slope in the
+        // next one.
+        let ray = Ray::new(Point::new(0.1, 0.8, 0.1), Vec3::new(1.0, 0.0, 0.2), 0.0);
+        let hit = field
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .expect("The ray hits the slope in the second cell.");
+        assert!((hit.p().x() - 1.6).abs() < 1e-4);
+        assert!((hit.p().z() - 0.4).abs() < 1e-4);
+
+        // The tangent follows the slope along the x-axis.
+        let tangent = hit.tangent();
+        assert!((tangent.y() / tangent.x() - 0.5).abs() < 1e-3);
+        assert!(tangent.z().abs() < 1e-3);
+    }
+}
@@ -2,16 +2,25 @@
 //! be hit by a [Ray]. It also contains the implementations of our geometric
 //! primitives which implement [Hittable].
 
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    fmt::Debug,
+    sync::{Arc, OnceLock},
+};
 
 use strum::IntoEnumIterator;
 
 use crate::{
     aabb::AABB,
+    color::Color,
     degrees_to_radians,
+    environment::EnvironmentMap,
     interval::Interval,
+    light::Light,
+    light_bvh::{LightBVH, LightBounds},
     material::Material,
+    onb::ONB,
     point::Point,
+    random_0_1_f32,
     ray::Ray,
     vec3::{Dimension, Unit3, Vec3},
     INFINITY, NEG_INFINITY, PI,
@@ -25,6 +34,9 @@ use crate::{
 pub struct HitRecord {
     p: Point,
     normal: Unit3,
+    tangent: Option<Unit3>,
+    /// The derivatives of the point with respect to `u` and `v`.
+    partials: Option<(Vec3, Vec3)>,
     material: Arc<dyn Material>,
     t: f32,
     u: f32,
@@ -52,6 +64,8 @@ impl HitRecord {
         HitRecord {
             p,
             normal,
+            tangent: None,
+            partials: None,
             material,
             t,
             u,
@@ -67,6 +81,8 @@ impl HitRecord {
         Self {
             p: self.p,
             normal: self.normal,
+            tangent: self.tangent,
+            partials: self.partials,
             material: Arc::clone(&self.material),
             t: self.t,
             u: self.u,
@@ -87,6 +103,47 @@ impl HitRecord {
         self.normal
     }
 
+    #[inline]
+    /// Return a tangent of the surface that was hit. It is perpendicular to
+    /// the normal and follows the direction in which the texture coordinate
+    /// `u` grows. For surfaces which do not provide a tangent, an arbitrary
+    /// tangent is returned.
+    pub fn tangent(&self) -> Unit3 {
+        self.tangent.unwrap_or_else(|| ONB::new(self.normal).u())
+    }
+
+    /// Set the tangent of the surface that was hit. The tangent is made
+    /// perpendicular to the normal.
+    pub fn set_tangent(&mut self, tangent: Vec3) {
+        self.tangent = Some(ONB::from_tangent(self.normal, tangent).u());
+    }
+
+    #[inline]
+    /// Return the derivatives `dp/du` and `dp/dv` of the point on the surface
+    /// with respect to the texture coordinates in world space, or [None] if
+    /// the surface does not provide them.
+    pub fn partials(&self) -> Option<(Vec3, Vec3)> {
+        self.partials
+    }
+
+    /// Set the derivatives `dp/du` and `dp/dv` of the point on the surface
+    /// with respect to the texture coordinates. The tangent follows `dp/du`.
+    pub fn set_partials(&mut self, dpdu: Vec3, dpdv: Vec3) {
+        self.partials = Some((dpdu, dpdv));
+        self.set_tangent(dpdu);
+    }
+
+    #[inline]
+    /// Return the normal of the surface that points to its outside, which is
+    /// the normal flipped to the other side for hits of the back face.
+    pub fn outward_normal(&self) -> Unit3 {
+        if self.front_face {
+            self.normal
+        } else {
+            -self.normal
+        }
+    }
+
     #[inline]
     /// The `t` which solves `Ray(t) = p` for the [Ray] that hit the surface.
     /// Note that the hit record does not have a reference to this ray. Thus,
@@ -114,6 +171,12 @@ impl HitRecord {
         self.material.as_ref()
     }
 
+    #[inline]
+    /// Replace the material of the surface that was hit.
+    pub fn set_material(&mut self, material: Arc<dyn Material>) {
+        self.material = material;
+    }
+
     #[inline]
     /// Return the texture coordinate `u`.
     pub fn u(&self) -> f32 {
@@ -126,6 +189,45 @@ impl HitRecord {
         self.v
     }
 
+    /// Returns true iff the material is opaque at the hit, such that the ray
+    /// stops here. Rays pass through materials that are partially transparent
+    /// at random with the probability of their transparency, see
+    /// [Material::opacity]. The decision only depends on the hit itself, such
+    /// that testing the same hit twice yields the same result.
+    pub fn is_opaque(&self) -> bool {
+        let opacity = self.material.opacity(self.u, self.v, self.p);
+        if opacity >= 1.0 {
+            return true;
+        }
+        if opacity <= 0.0 {
+            return false;
+        }
+        // Hash the distance and texture coordinates, which do not change when
+        // the hit is transformed, into a number in [0, 1).
+        let mut hash = (self.t.to_bits() as u64) << 32 ^ self.u.to_bits() as u64;
+        hash ^= (self.v.to_bits() as u64).rotate_left(17);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^= hash >> 31;
+        ((hash >> 40) as f32 / (1u64 << 24) as f32) < opacity
+    }
+
+    #[inline]
+    /// Replace the normal that is used for shading, e.g., by a normal or bump
+    /// map. The shading normal should face the same side as the normal of
+    /// the surface.
+    pub fn set_shading_normal(&mut self, normal: Unit3) {
+        self.normal = normal;
+    }
+
+    #[inline]
+    /// Flip the record such that it describes a hit of the surface turned
+    /// inside out. I.e., the front face becomes the back face and vice versa.
+    /// The normal still points against the ray and is thus unchanged.
+    pub fn flip(&mut self) {
+        self.front_face = !self.front_face;
+    }
+
     fn face_normal(ray: &Ray, outward_normal: Unit3) -> (bool, Unit3) {
         // SAFETY: We assume that outward_normal has unit length.
         let front_face = ray.direction().dot(*outward_normal) < 0.0;
@@ -138,6 +240,41 @@ impl HitRecord {
     }
 }
 
+#[derive(Clone, Debug)]
+/// A span along a [Ray] during which the ray is inside of a closed [Hittable].
+/// The span is delimited by the [HitRecord] of the surface where the ray
+/// enters the object and the [HitRecord] where it exits the object again.
+pub struct Span {
+    enter: HitRecord,
+    exit: HitRecord,
+}
+
+impl Span {
+    /// Create a new span from the records where the ray enters and exits.
+    pub fn new(enter: HitRecord, exit: HitRecord) -> Self {
+        Self { enter, exit }
+    }
+
+    #[inline]
+    /// Return the record where the ray enters the object.
+    pub fn enter(&self) -> &HitRecord {
+        &self.enter
+    }
+
+    #[inline]
+    /// Return the record where the ray exits the object.
+    pub fn exit(&self) -> &HitRecord {
+        &self.exit
+    }
+
+    #[inline]
+    /// Consume the span and return the records where the ray enters and
+    /// exits the object.
+    pub fn into_records(self) -> (HitRecord, HitRecord) {
+        (self.enter, self.exit)
+    }
+}
+
 /// A trait that defines the behavior objects that can be 'hit' by a [Ray]
 /// must implement.
 ///
@@ -152,8 +289,57 @@ pub trait Hittable: Debug + Send + Sync {
 
     /// Return a reference to the bounding box of the hittable.
     fn bounding_box(&self) -> &AABB;
+
+    /// Compute all [Span]s along the whole line of `ray` during which the
+    /// ray is inside of `self`, ordered by their distance along the ray.
+    /// This is only meaningful for closed objects.
+    ///
+    /// The default implementation repeatedly calls [Hittable::hit] and pairs
+    /// up hits of front faces with the following hits of back faces.
+    /// Implementors can override this with a more efficient computation.
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let mut spans = Vec::new();
+        let mut enter: Option<HitRecord> = None;
+        let mut t_min = NEG_INFINITY;
+        while let Some(hit_record) = self.hit(ray, Interval::new(t_min, INFINITY)) {
+            t_min = hit_record.t() + SPAN_EPSILON * hit_record.t().abs().max(1.0);
+            match enter.take() {
+                None if hit_record.front_face() => enter = Some(hit_record),
+                Some(enter_record) if !hit_record.front_face() => {
+                    spans.push(Span::new(enter_record, hit_record))
+                }
+                // Ignore unmatched back faces and repeated front faces, which
+                // can occur when a ray grazes an edge of the surface.
+                other => enter = other,
+            }
+        }
+        spans
+    }
+}
+
+/// Compute the closest hit of `object` by `ray` in `ray_t` that is opaque,
+/// see [HitRecord::is_opaque]. Hits of transparent parts of a surface, e.g.,
+/// the cutouts of a leaf texture, are skipped.
+pub fn hit_opaque(object: &dyn Hittable, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+    let mut ray_t = ray_t;
+    loop {
+        let hit_record = object.hit(ray, ray_t)?;
+        if hit_record.is_opaque() {
+            return Some(hit_record);
+        }
+        // Some hittables include the ends of the interval, so continue right
+        // after the skipped hit.
+        ray_t = Interval::new(hit_record.t().next_up(), ray_t.max());
+    }
 }
 
+/// The minimum distance between two hits when enumerating all hits along a
+/// ray, relative to the distance of the previous hit.
+const SPAN_EPSILON: f32 = 0.0001;
+
+/// The number of points over which a sphere light estimates its power.
+const POWER_SAMPLES: u32 = 16;
+
 #[derive(Clone, Debug)]
 /// A struct that implements a sphere in the world
 pub struct Sphere {
@@ -224,13 +410,43 @@ impl Sphere {
             .map_or(self.center, |v| self.center + at_time * v)
     }
 
-    fn get_sphere_uv(p: Point) -> (f32, f32) {
+    /// Compute the texture coordinates of the point `p` on the unit sphere.
+    pub(crate) fn get_sphere_uv(p: Point) -> (f32, f32) {
         let theta = f32::acos(-p.y());
         let phi = f32::atan2(-p.z(), p.x()) + PI;
         let u = phi / (2.0 * PI);
         let v = theta / PI;
         (u, v)
     }
+
+    /// Compute the derivatives `dp/du` and `dp/dv` at the point `p` on the
+    /// unit sphere for a sphere of the given `radius`. They vanish at the
+    /// poles.
+    fn get_sphere_partials(p: Point, radius: f32) -> (Vec3, Vec3) {
+        let dpdu = 2.0 * PI * radius * Vec3::new(p.z(), 0.0, -p.x());
+        let sin_theta = (p.x() * p.x() + p.z() * p.z()).sqrt().max(1e-6);
+        let dpdv = PI
+            * radius
+            * Vec3::new(
+                -p.y() * p.x() / sin_theta,
+                sin_theta,
+                -p.y() * p.z() / sin_theta,
+            );
+        (dpdu, dpdv)
+    }
+
+    /// The solid angle that the sphere at time 0 covers seen from `p`, or
+    /// [None] if `p` is inside of the sphere.
+    fn solid_angle(&self, p: Point) -> Option<f32> {
+        let distance_squared = (self.sphere_center(0.0) - p).length_squared();
+        let sin_squared = self.radius * self.radius / distance_squared;
+        if sin_squared >= 1.0 {
+            return None;
+        }
+        // Compute `1 - cos(theta)` without cancellation for small spheres.
+        let h = sin_squared / (1.0 + (1.0 - sin_squared).sqrt());
+        Some(2.0 * PI * h)
+    }
 }
 
 impl Hittable for Sphere {
@@ -261,20 +477,115 @@ impl Hittable for Sphere {
         // point where the ray intersected the spheres surface. Thus, dividing
         // by the radius ensures it is of unit length.
         let normal = Unit3::new_unchecked(normal);
-        Some(HitRecord::new(
-            ray,
-            p,
-            normal,
-            root,
-            u,
-            v,
-            self.material.clone(),
-        ))
+        let mut hit_record = HitRecord::new(ray, p, normal, root, u, v, self.material.clone());
+        let (dpdu, dpdv) = Self::get_sphere_partials(Point::from(*normal), self.radius);
+        hit_record.set_partials(dpdu, dpdv);
+        Some(hit_record)
     }
 
     fn bounding_box(&self) -> &AABB {
         &self.bounding_box
     }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let center = self.sphere_center(ray.time());
+        let oc = center - *ray.origin();
+        let a = ray.direction().length_squared();
+        let h = ray.direction().dot(oc);
+        let c = oc.length_squared() - self.radius * self.radius;
+        let discriminant = h * h - a * c;
+        if discriminant <= 0.0 {
+            return Vec::new();
+        }
+        let sqrtd = discriminant.sqrt();
+
+        let record = |root: f32| {
+            let p = ray.at(root);
+            let normal = (p - center) / self.radius;
+            let (u, v) = Self::get_sphere_uv(Point::from(normal));
+            // SAFETY: See [Sphere::hit].
+            let normal = Unit3::new_unchecked(normal);
+            let mut hit_record = HitRecord::new(ray, p, normal, root, u, v, self.material.clone());
+            let (dpdu, dpdv) = Self::get_sphere_partials(Point::from(*normal), self.radius);
+            hit_record.set_partials(dpdu, dpdv);
+            hit_record
+        };
+        vec![Span::new(record((h - sqrtd) / a), record((h + sqrtd) / a))]
+    }
+}
+
+/// A sphere with an emissive material, e.g., a
+/// [DiffuseLight](crate::material::DiffuseLight), is an area light when it is
+/// pushed to the world as both an object and a light. Moving spheres are
+/// sampled at their position at time 0.
+impl Light for Sphere {
+    fn sample(&self, p: Point) -> Option<(Vec3, f32, Color)> {
+        // Sample the cone of directions towards the sphere uniformly, where
+        // `h = 1 - cos(theta)`.
+        let solid_angle = self.solid_angle(p)?;
+        let h = random_0_1_f32() * solid_angle / (2.0 * PI);
+        let sin_theta = (h * (2.0 - h)).sqrt();
+        let phi = 2.0 * PI * random_0_1_f32();
+        let local = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), 1.0 - h);
+        let to_center = (self.sphere_center(0.0) - p).unit();
+        let direction = ONB::new(to_center).to_world(local);
+
+        let ray = Ray::new(p, direction, 0.0);
+        let hit_record = self.hit(&ray, Interval::new(0.0, INFINITY))?;
+        let radiance = self.material.emitted(&ray, &hit_record);
+        if radiance.luminance() <= 0.0 {
+            return None;
+        }
+        Some((direction, hit_record.t(), radiance * solid_angle))
+    }
+
+    fn pdf(&self, p: Point, direction: Vec3) -> f32 {
+        let Some(solid_angle) = self.solid_angle(p) else {
+            return 0.0;
+        };
+        let ray = Ray::new(p, direction, 0.0);
+        if self.hit(&ray, Interval::new(0.0, INFINITY)).is_none() {
+            return 0.0;
+        }
+        1.0 / solid_angle
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        let distance = (point - self.sphere_center(0.0)).length();
+        (distance - self.radius).abs() <= 1e-3 * self.radius.max(1.0)
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        // Estimate the radiance by the average light leaving points spread
+        // evenly over the sphere on a Fibonacci spiral, so that a textured
+        // light which is dark in places is not missed.
+        let center = self.sphere_center(0.0);
+        let golden_angle = PI * (3.0 - 5.0_f32.sqrt());
+        let radiance = (0..POWER_SAMPLES)
+            .map(|i| {
+                let z = 1.0 - (2.0 * i as f32 + 1.0) / POWER_SAMPLES as f32;
+                let r = (1.0 - z * z).sqrt();
+                let phi = golden_angle * i as f32;
+                let outward = Vec3::new(r * phi.cos(), r * phi.sin(), z);
+                let origin = center + 2.0 * self.radius * outward;
+                let ray = Ray::new(origin, center - origin, 0.0);
+                self.hit(&ray, Interval::new(0.0, INFINITY))
+                    .map_or(0.0, |hit_record| {
+                        self.material.emitted(&ray, &hit_record).luminance()
+                    })
+            })
+            .sum::<f32>()
+            / POWER_SAMPLES as f32;
+        let area = 4.0 * PI * self.radius * self.radius;
+        Some(LightBounds::new(
+            self.bounding_box,
+            Vec3::new(0.0, 0.0, 1.0),
+            -1.0,
+            0.0,
+            PI * area * radiance,
+            false,
+        ))
+    }
 }
 
 #[derive(Default, Debug)]
@@ -284,6 +595,12 @@ pub struct World {
     objects: Vec<Arc<dyn Hittable>>,
     /// The bounding box for this world.
     bounding_box: AABB,
+    /// The lights that are sampled explicitly at hits.
+    lights: Vec<Arc<dyn Light>>,
+    /// The hierarchy of the lights, which is built when it is first used.
+    light_bvh: OnceLock<LightBVH>,
+    /// The light arriving from rays that leave the world.
+    environment: Option<Arc<EnvironmentMap>>,
 }
 
 impl World {
@@ -292,6 +609,9 @@ impl World {
         Self {
             objects: Vec::new(),
             bounding_box: AABB::default(),
+            lights: Vec::new(),
+            light_bvh: OnceLock::new(),
+            environment: None,
         }
     }
 
@@ -310,6 +630,37 @@ impl World {
     pub fn extend(&mut self, other: impl IntoIterator<Item = Arc<dyn Hittable>>) {
         self.objects.extend(other)
     }
+
+    /// Add a new light to the world, which is sampled explicitly at hits.
+    /// Lights are not objects, i.e., rays do not hit them. Objects that are
+    /// lights, e.g., an emissive [Quad](crate::quad::Quad), are pushed as
+    /// both.
+    pub fn push_light(&mut self, light: Arc<dyn Light>) {
+        self.lights.push(light);
+        self.light_bvh = OnceLock::new();
+    }
+
+    /// The lights in the world.
+    pub fn lights(&self) -> &[Arc<dyn Light>] {
+        &self.lights
+    }
+
+    /// The hierarchy of the lights in the world, which picks one of them at
+    /// every hit.
+    pub fn light_bvh(&self) -> &LightBVH {
+        self.light_bvh.get_or_init(|| LightBVH::new(&self.lights))
+    }
+
+    /// Surround the world by an environment map, which replaces the
+    /// background of the camera and is sampled explicitly at every hit.
+    pub fn set_environment(&mut self, environment: Arc<EnvironmentMap>) {
+        self.environment = Some(environment)
+    }
+
+    /// The environment map around the world, if any.
+    pub fn environment(&self) -> Option<&EnvironmentMap> {
+        self.environment.as_deref()
+    }
 }
 
 impl Hittable for World {
@@ -318,7 +669,7 @@ impl Hittable for World {
         let mut result = None;
         for hittable in self.objects.iter() {
             let interval = Interval::new(ray_t.min(), closest_so_far);
-            if let Some(hit_record) = hittable.hit(ray, interval) {
+            if let Some(hit_record) = hit_opaque(hittable.as_ref(), ray, interval) {
                 closest_so_far = hit_record.t();
                 result = Some(hit_record);
             }
@@ -353,7 +704,7 @@ impl Translate {
 
 impl Hittable for Translate {
     fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
-        let offset_ray = Ray::new(*ray.origin() - self.offset, *ray.direction(), ray.time());
+        let offset_ray = ray.spawn(*ray.origin() - self.offset, *ray.direction());
         self.object.hit(&offset_ray, ray_t).map(|mut hit_rec| {
             let new_p = hit_rec.p() + self.offset;
             hit_rec.p = new_p;
@@ -433,7 +784,7 @@ impl Hittable for RotationY {
             (self.sin_theta * ray.direction().x()) + (self.cos_theta * ray.direction().z()),
         );
 
-        let rotated_ray = Ray::new(origin, direction, ray.time());
+        let rotated_ray = ray.spawn(origin, direction);
 
         self.object.hit(&rotated_ray, ray_t).map(|mut hit_rec| {
             hit_rec.p = Point::new(
@@ -446,6 +797,19 @@ impl Hittable for RotationY {
                 hit_rec.normal().y(),
                 (-self.sin_theta * hit_rec.normal().x()) + (self.cos_theta * hit_rec.normal().z()),
             ));
+            let rotate = |v: Vec3| {
+                Vec3::new(
+                    (self.cos_theta * v.x()) + (self.sin_theta * v.z()),
+                    v.y(),
+                    (-self.sin_theta * v.x()) + (self.cos_theta * v.z()),
+                )
+            };
+            hit_rec.tangent = hit_rec
+                .tangent
+                .map(|tangent| Unit3::new_unchecked(rotate(*tangent)));
+            hit_rec.partials = hit_rec
+                .partials
+                .map(|(dpdu, dpdv)| (rotate(dpdu), rotate(dpdv)));
             hit_rec
         })
     }
@@ -0,0 +1,269 @@
+//! This module contains a parser for photometric profiles in the IES LM-63
+//! format, which describe how the intensity of a light fixture varies with
+//! the direction. See [IesProfile].
+
+use std::path::Path;
+
+use crate::{vec3::Vec3, PI};
+
+#[derive(Debug, Clone)]
+/// The angular distribution of the intensity of a light fixture, read from an
+/// IES LM-63 file. Only type C photometry is supported, which is used by
+/// almost all architectural fixtures.
+///
+/// The vertical angle is 0 degrees straight down from the fixture, i.e., at
+/// its nadir, and 180 degrees straight up. The horizontal angle turns around
+/// the nadir, starting at the C0 plane along the length of the fixture. Files
+/// may only contain the horizontal angles up to the symmetry of the fixture,
+/// which are mirrored onto the other angles.
+pub struct IesProfile {
+    /// The vertical angles in degrees, in increasing order.
+    vertical_angles: Vec<f32>,
+    /// The horizontal angles in degrees, in increasing order.
+    horizontal_angles: Vec<f32>,
+    /// The intensities in candela for each horizontal angle, for each
+    /// vertical angle.
+    candela: Vec<Vec<f32>>,
+    /// The largest intensity in candela.
+    max_candela: f32,
+}
+
+impl IesProfile {
+    /// Loads a profile from an IES file. Panics if the loading or the parsing
+    /// fails.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let text = std::fs::read_to_string(path).expect("Failed to load IES file.");
+        Self::parse(&text).expect("Malformed IES file.")
+    }
+
+    /// Parse the contents of an IES file. Returns [None] if the file is
+    /// malformed, does not use type C photometry, or its horizontal angles
+    /// do not cover one of the symmetries of [IesProfile::intensity].
+    pub fn parse(text: &str) -> Option<Self> {
+        // The header consists of keywords up to the line with the tilt.
+        let mut lines = text.lines();
+        let tilt = lines
+            .find(|line| line.trim_start().starts_with("TILT="))?
+            .trim_start()
+            .trim_start_matches("TILT=")
+            .trim();
+        let mut numbers = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(|token| token.parse::<f32>().ok());
+        let mut next = || numbers.next().flatten();
+
+        // Skip the tilt data, which is only given for lamps whose output
+        // depends on how they are mounted.
+        if tilt == "INCLUDE" {
+            let _geometry = next()?;
+            let pairs = next()? as usize;
+            for _ in 0..2 * pairs {
+                next()?;
+            }
+        }
+
+        let _lamps = next()?;
+        let _lumens_per_lamp = next()?;
+        let multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let photometric_type = next()?;
+        let _units = next()?;
+        let _size = [next()?, next()?, next()?];
+        let ballast_factor = next()?;
+        let _ballast_lamp_factor = next()?;
+        let _input_watts = next()?;
+        if photometric_type != 1.0 || vertical_count == 0 || horizontal_count == 0 {
+            return None;
+        }
+
+        let vertical_angles = (0..vertical_count)
+            .map(|_| next())
+            .collect::<Option<Vec<_>>>()?;
+        let horizontal_angles = (0..horizontal_count)
+            .map(|_| next())
+            .collect::<Option<Vec<_>>>()?;
+        let (first, last) = (
+            horizontal_angles[0],
+            horizontal_angles[horizontal_count - 1],
+        );
+        let symmetric = if first == 0.0 {
+            [0.0, 90.0, 180.0, 360.0].contains(&last)
+        } else {
+            first == 90.0 && last == 270.0
+        };
+        if !symmetric {
+            return None;
+        }
+        let scale = multiplier * ballast_factor;
+        let candela = (0..horizontal_count)
+            .map(|_| {
+                (0..vertical_count)
+                    .map(|_| next().map(|value| value * scale))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let max_candela = candela
+            .iter()
+            .flatten()
+            .fold(0.0_f32, |max, &value| max.max(value));
+
+        Some(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+            max_candela,
+        })
+    }
+
+    /// The largest intensity of the profile in candela.
+    pub fn max_intensity(&self) -> f32 {
+        self.max_candela
+    }
+
+    /// The intensity in candela at the `vertical` and `horizontal` angle in
+    /// degrees. The intensity is interpolated between the angles of the file,
+    /// and it is 0 outside of the vertical angles of the file.
+    pub fn intensity(&self, vertical: f32, horizontal: f32) -> f32 {
+        let Some((v0, v1, tv)) = Self::locate(&self.vertical_angles, vertical) else {
+            return 0.0;
+        };
+
+        // Files only contain the horizontal angles up to the symmetry of the
+        // fixture, i.e., a single angle for rotational symmetry, 0 to 90
+        // degrees for quadrants, 0 to 180 or 90 to 270 degrees for two
+        // halves, or all.
+        let horizontal = horizontal.rem_euclid(360.0);
+        let first = self.horizontal_angles[0];
+        let last = *self.horizontal_angles.last().unwrap_or(&0.0);
+        let horizontal = if first == 90.0 {
+            if (90.0..=270.0).contains(&horizontal) {
+                horizontal
+            } else {
+                (180.0 - horizontal).rem_euclid(360.0)
+            }
+        } else if last <= 0.0 {
+            0.0
+        } else if last <= 90.0 {
+            let h = if horizontal > 180.0 {
+                360.0 - horizontal
+            } else {
+                horizontal
+            };
+            if h > 90.0 {
+                180.0 - h
+            } else {
+                h
+            }
+        } else if last <= 180.0 && horizontal > 180.0 {
+            360.0 - horizontal
+        } else {
+            horizontal
+        };
+        let horizontal = horizontal.clamp(self.horizontal_angles[0], last);
+        let (h0, h1, th) = Self::locate(&self.horizontal_angles, horizontal).unwrap_or((0, 0, 0.0));
+
+        let at = |h: usize| (1.0 - tv) * self.candela[h][v0] + tv * self.candela[h][v1];
+        (1.0 - th) * at(h0) + th * at(h1)
+    }
+
+    /// The intensity towards `direction` relative to the largest intensity,
+    /// where the direction is given in a frame whose z-axis points to the
+    /// nadir and whose x-axis lies in the C0 plane.
+    pub(crate) fn relative_intensity(&self, direction: Vec3) -> f32 {
+        if self.max_candela <= 0.0 {
+            return 0.0;
+        }
+        let direction = direction.unit();
+        let vertical = direction.z().clamp(-1.0, 1.0).acos() * 180.0 / PI;
+        let horizontal = f32::atan2(direction.y(), direction.x()) * 180.0 / PI;
+        self.intensity(vertical, horizontal) / self.max_candela
+    }
+
+    /// Find the two neighbouring `angles` around `angle` and the fraction of
+    /// the way between them. Returns [None] if `angle` is outside.
+    fn locate(angles: &[f32], angle: f32) -> Option<(usize, usize, f32)> {
+        let first = *angles.first()?;
+        let last = *angles.last()?;
+        if angle < first || angle > last {
+            return None;
+        }
+        if angles.len() == 1 {
+            return Some((0, 0, 0.0));
+        }
+        let i = angles
+            .partition_point(|&a| a <= angle)
+            .clamp(1, angles.len() - 1);
+        let (a0, a1) = (angles[i - 1], angles[i]);
+        let t = if a1 > a0 {
+            (angle - a0) / (a1 - a0)
+        } else {
+            0.0
+        };
+        Some((i - 1, i, t))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::IesProfile;
+
+    const QUADRANT: &str = "IESNA:LM-63-2002
+[TEST] A fixture that is brighter along its length
+[MANUFAC] None
+TILT=INCLUDE
+1
+2
+0 90
+1.0 0.9
+1 1000 2.0 3 2 1 2 0.5 1.2 0
+1.0 1.0 50
+0 45 90
+0, 90
+100 80 0
+60 40 0
+";
+
+    #[test]
+    fn parse_and_interpolate() {
+        let profile = IesProfile::parse(QUADRANT).unwrap();
+        assert_eq!(profile.max_intensity(), 200.0);
+        assert_eq!(profile.intensity(0.0, 0.0), 200.0);
+        assert_eq!(profile.intensity(22.5, 0.0), 180.0);
+        assert_eq!(profile.intensity(45.0, 45.0), 120.0);
+        assert_eq!(profile.intensity(120.0, 0.0), 0.0);
+
+        // The quadrant is mirrored onto the other three quadrants.
+        assert_eq!(profile.intensity(45.0, 90.0), 80.0);
+        assert_eq!(profile.intensity(45.0, 180.0), 160.0);
+        assert_eq!(profile.intensity(45.0, 270.0), 80.0);
+        assert_eq!(profile.intensity(45.0, -90.0), 80.0);
+
+        assert!(IesProfile::parse("TILT=NONE\n1 1000 1").is_none());
+    }
+
+    #[test]
+    fn mirror_halves_from_90_to_270_degrees() {
+        let half = "TILT=NONE
+1 1000 1 2 3 1 2 0.5 1.2 0
+1.0 1.0 50
+0 90
+90 180 270
+10 0
+30 0
+50 0
+";
+        let profile = IesProfile::parse(half).unwrap();
+        assert_eq!(profile.intensity(0.0, 180.0), 30.0);
+        assert_eq!(profile.intensity(0.0, 135.0), 20.0);
+        // The half is mirrored about the plane of 90 and 270 degrees.
+        assert_eq!(profile.intensity(0.0, 45.0), 20.0);
+        assert_eq!(profile.intensity(0.0, 0.0), 30.0);
+        assert_eq!(profile.intensity(0.0, 315.0), 40.0);
+
+        // Other ranges of horizontal angles are rejected.
+        let shifted = half.replace("90 180 270", "45 180 270");
+        assert!(IesProfile::parse(&shifted).is_none());
+    }
+}
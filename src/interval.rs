@@ -32,6 +32,15 @@ impl Interval {
         Interval { min, max }
     }
 
+    #[inline]
+    /// Create a new interval which is the overlap of both intervals `a` and `b`.
+    /// The result is empty if the intervals do not overlap.
+    pub fn overlapping(a: &Interval, b: &Interval) -> Self {
+        let min = if a.min >= b.min { a.min } else { b.min };
+        let max = if a.max <= b.max { a.max } else { b.max };
+        Interval { min, max }
+    }
+
     #[inline]
     /// Get `min` of the interval `[min, max]`.
     pub fn min(&self) -> f32 {
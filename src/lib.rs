@@ -14,18 +14,34 @@
 )]
 
 pub mod aabb;
+pub mod background;
 pub mod bvh;
 pub mod camera;
 pub mod color;
 pub mod constant_medium;
+pub mod csg;
+pub mod curve;
+pub mod environment;
+pub mod heightfield;
 pub mod hittable;
+pub mod ies;
 pub mod interval;
+pub mod light;
+pub mod light_bvh;
 pub mod material;
+pub mod microfacet;
+pub mod onb;
 pub mod perlin;
 pub mod point;
+pub mod principled;
 pub mod quad;
 pub mod ray;
+pub mod sdf;
+pub mod sky;
+pub mod spectrum;
+pub mod subsurface;
 pub mod texture;
+pub mod thin_film;
 pub mod vec3;
 
 use std::f32;
@@ -90,6 +106,17 @@ pub fn random_unit_vector() -> Unit3 {
     random_in_unit_sphere().unit()
 }
 
+/// Generates a random direction in the hemisphere around the z-axis whose
+/// density is proportional to the cosine of its angle to the z-axis, i.e.,
+/// `cos(theta) / PI`.
+pub fn random_cosine_direction() -> Vec3 {
+    let r1 = random_0_1_f32();
+    let r2 = random_0_1_f32();
+    let phi = 2.0 * PI * r1;
+    let r = r2.sqrt();
+    Vec3::new(phi.cos() * r, phi.sin() * r, (1.0 - r2).sqrt())
+}
+
 /// Generates a random unit vector in the disk with radius 1.0. Note that the
 /// disk lies in the x and y plane.
 pub fn random_in_unit_disk() -> Vec3 {
@@ -0,0 +1,368 @@
+//! This module defines a trait for [Light]s, which the camera samples
+//! explicitly at hits instead of waiting for scattered rays to hit them.
+//! The module also contains idealized lights without a surface, which could
+//! never be hit by a ray: [PointLight], [SpotLight], and [DirectionalLight],
+//! as well as the [SunLight], a small disk infinitely far away.
+
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{
+    aabb::AABB, color::Color, degrees_to_radians, ies::IesProfile, light_bvh::LightBounds,
+    onb::ONB, point::Point, random_0_1_f32, vec3::Vec3, INFINITY, PI,
+};
+
+/// A trait for lights that can be sampled from a point in the world.
+///
+/// Implementing [Send] and [Sync] is required to concurrently render pixels.
+pub trait Light: Debug + Send + Sync {
+    /// Sample the light that arrives at `p`. Returns the unit direction from
+    /// `p` towards the light, the distance to the light, and the radiance
+    /// arriving from that direction divided by the probability of the sample.
+    /// The light is blocked by any object that is hit closer than the
+    /// distance.
+    fn sample(&self, p: Point) -> Option<(Vec3, f32, Color)>;
+
+    /// The probability density with respect to the solid angle at `p` that
+    /// [Light::sample] picks `direction`. Lights that shine from a single
+    /// point or direction, which no scattered ray can find, return 0.
+    fn pdf(&self, _p: Point, _direction: Vec3) -> f32 {
+        0.0
+    }
+
+    /// The radiance that arrives from `direction` at rays that leave the
+    /// world, for lights that are infinitely far away and can be found by
+    /// scattered rays.
+    fn radiance(&self, _direction: Vec3) -> Color {
+        Color::black()
+    }
+
+    /// Bound where the light is and into which directions it emits, so that
+    /// a [LightBVH](crate::light_bvh::LightBVH) can pick it among many
+    /// lights. Lights that are infinitely far away return [None] and are
+    /// sampled at every hit.
+    fn bounds(&self) -> Option<LightBounds> {
+        None
+    }
+
+    /// Whether `point` lies on the light, so that a
+    /// [LightBVH](crate::light_bvh::LightBVH) can tell the light that a ray
+    /// hit apart from other lights whose bounds contain the point. Lights
+    /// that no ray can hit claim every point.
+    fn contains(&self, _point: Point) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A light that shines equally into all directions from a single point,
+/// unless it is shaped by a photometric profile.
+pub struct PointLight {
+    position: Point,
+    intensity: Color,
+    profile: Option<Arc<IesProfile>>,
+}
+
+impl PointLight {
+    /// Create a new point light at `position`. The light arriving at a point
+    /// is `intensity` divided by the squared distance to the light.
+    pub fn new(position: Point, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+            profile: None,
+        }
+    }
+
+    /// Shape the light by a photometric profile, whose nadir points down the
+    /// y-axis and whose C0 plane contains the x-axis. The intensity of the
+    /// light is reached where the profile is brightest.
+    pub fn with_profile(mut self, profile: Arc<IesProfile>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, p: Point) -> Option<(Vec3, f32, Color)> {
+        let to_light = self.position - p;
+        let distance_squared = to_light.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let profile = match &self.profile {
+            Some(profile) => {
+                let (x, y, z) = (-direction.x(), -direction.y(), -direction.z());
+                profile.relative_intensity(Vec3::new(x, z, -y))
+            }
+            None => 1.0,
+        };
+        Some((
+            direction,
+            distance,
+            self.intensity * (profile / distance_squared),
+        ))
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let bounds = AABB::from_points(self.position, self.position);
+        let axis = Vec3::new(0.0, 0.0, 1.0);
+        let power = 4.0 * PI * self.intensity.luminance();
+        Some(LightBounds::new(bounds, axis, -1.0, 0.0, power, false))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A point light that only shines into a cone around its axis.
+pub struct SpotLight {
+    position: Point,
+    /// The unit axis of the cone, pointing away from the light.
+    axis: Vec3,
+    intensity: Color,
+    /// The cosines of the angles to the axis where the light starts to fall
+    /// off, and where it is cut off.
+    cos_falloff: f32,
+    cos_cone: f32,
+    profile: Option<Arc<IesProfile>>,
+    /// A direction in the C0 plane of the profile.
+    c0: Vec3,
+}
+
+impl SpotLight {
+    /// Create a new spotlight at `position` that points at `target`.
+    ///
+    /// * `intensity` - The intensity along the axis, see [PointLight::new].
+    /// * `cone_angle` - The angle in degrees between the axis and the edge of
+    ///   the cone, outside of which the light is cut off.
+    /// * `falloff_start` - The angle in degrees from which the light smoothly
+    ///   falls off towards the edge of the cone.
+    pub fn new(
+        position: Point,
+        target: Point,
+        intensity: Color,
+        cone_angle: f32,
+        falloff_start: f32,
+    ) -> Self {
+        Self {
+            position,
+            axis: *(target - position).unit(),
+            intensity,
+            cos_falloff: degrees_to_radians(falloff_start.min(cone_angle)).cos(),
+            cos_cone: degrees_to_radians(cone_angle).cos(),
+            profile: None,
+            c0: Vec3::new(1.0, 0.0, 0.0),
+        }
+    }
+
+    /// Shape the light inside of the cone by a photometric profile, whose
+    /// nadir points along the axis and whose C0 plane contains the direction
+    /// `c0`, e.g., along the length of the fixture. The intensity of the
+    /// light is reached where the profile is brightest.
+    pub fn with_profile(mut self, profile: Arc<IesProfile>, c0: Vec3) -> Self {
+        self.profile = Some(profile);
+        self.c0 = c0;
+        self
+    }
+
+    /// The fraction of the light that is emitted at the angle `cos_theta` to
+    /// the axis.
+    fn falloff(&self, cos_theta: f32) -> f32 {
+        if cos_theta < self.cos_cone {
+            return 0.0;
+        }
+        if cos_theta >= self.cos_falloff {
+            return 1.0;
+        }
+        let t = (cos_theta - self.cos_cone) / (self.cos_falloff - self.cos_cone);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, p: Point) -> Option<(Vec3, f32, Color)> {
+        let to_light = self.position - p;
+        let distance_squared = to_light.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let mut falloff = self.falloff(-direction.dot(self.axis));
+        if let Some(profile) = &self.profile {
+            let frame = ONB::from_tangent(self.axis.unit(), self.c0);
+            falloff *= profile.relative_intensity(frame.to_local(-direction));
+        }
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some((
+            direction,
+            distance,
+            self.intensity * (falloff / distance_squared),
+        ))
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let bounds = AABB::from_points(self.position, self.position);
+        let power = 2.0 * PI * (1.0 - self.cos_cone) * self.intensity.luminance();
+        Some(LightBounds::new(
+            bounds,
+            self.axis,
+            self.cos_cone,
+            1.0,
+            power,
+            false,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A light that is infinitely far away, like the sun, such that its light
+/// arrives everywhere from the same direction.
+pub struct DirectionalLight {
+    /// The unit direction from the world towards the light.
+    to_light: Vec3,
+    radiance: Color,
+}
+
+impl DirectionalLight {
+    /// Create a new directional light whose light travels into `direction`
+    /// and arrives with `radiance` at surfaces that face it.
+    pub fn new(direction: Vec3, radiance: Color) -> Self {
+        Self {
+            to_light: -*direction.unit(),
+            radiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _p: Point) -> Option<(Vec3, f32, Color)> {
+        Some((self.to_light, INFINITY, self.radiance))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A [DirectionalLight] with the extent of a small disk in the sky, like the
+/// sun, which casts shadows with soft edges and is seen by rays that leave
+/// the world.
+pub struct SunLight {
+    /// The unit direction from the world towards the center of the disk.
+    to_light: Vec3,
+    /// The solid angle of the disk, i.e., `2 * PI * (1 - cos(radius))`,
+    /// which is computed without cancellation for small disks.
+    solid_angle: f32,
+    radiance: Color,
+}
+
+impl SunLight {
+    /// Create a new sun whose light travels into `direction`.
+    ///
+    /// * `angular_diameter` - The angle in degrees that the disk spans, e.g.,
+    ///   about 0.53 degrees for the sun seen from the earth.
+    /// * `radiance` - The radiance of the disk. The irradiance on surfaces that
+    ///   face the sun is the radiance times the solid angle of the disk.
+    pub fn new(direction: Vec3, angular_diameter: f32, radiance: Color) -> Self {
+        let radius = degrees_to_radians(angular_diameter / 2.0);
+        let half_sin = (radius / 2.0).sin();
+        Self {
+            to_light: -*direction.unit(),
+            solid_angle: 4.0 * PI * half_sin * half_sin,
+            radiance,
+        }
+    }
+
+    /// Whether `direction` points into the disk. The squared distance between
+    /// unit vectors is `2 * (1 - cos(theta))`, which does not lose precision
+    /// like the cosine itself for small disks.
+    fn contains(&self, direction: Vec3) -> bool {
+        (*direction.unit() - self.to_light).length_squared() <= self.solid_angle / PI
+    }
+}
+
+impl Light for SunLight {
+    fn sample(&self, _p: Point) -> Option<(Vec3, f32, Color)> {
+        // Sample the cone of the disk uniformly, where `h = 1 - cos(theta)`.
+        let h = random_0_1_f32() * self.solid_angle / (2.0 * PI);
+        let sin_theta = (h * (2.0 - h)).sqrt();
+        let phi = 2.0 * PI * random_0_1_f32();
+        let local = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), 1.0 - h);
+        let direction = ONB::new(self.to_light.unit()).to_world(local);
+        Some((direction, INFINITY, self.radiance * self.solid_angle))
+    }
+
+    fn pdf(&self, _p: Point, direction: Vec3) -> f32 {
+        if !self.contains(direction) {
+            return 0.0;
+        }
+        1.0 / self.solid_angle
+    }
+
+    fn radiance(&self, direction: Vec3) -> Color {
+        if !self.contains(direction) {
+            return Color::black();
+        }
+        self.radiance
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{color::Color, ies::IesProfile, point::Point, vec3::Vec3};
+
+    use super::{Light, PointLight, SpotLight};
+
+    #[test]
+    fn spot_light_is_a_cut_off_point_light() {
+        let position = Point::new(0.0, 4.0, 0.0);
+        let intensity = Color::new(8.0, 8.0, 8.0);
+        let point = PointLight::new(position, intensity);
+        let spot = SpotLight::new(position, Point::new(0.0, 0.0, 0.0), intensity, 30.0, 20.0);
+
+        // Inside of the inner cone, both lights agree.
+        let p = Point::new(1.0, 0.0, 0.0);
+        let (direction, distance, radiance) = point.sample(p).unwrap();
+        let (_, _, spot_radiance) = spot.sample(p).unwrap();
+        assert!((distance - 17.0_f32.sqrt()).abs() < 1e-5);
+        assert!((direction.length() - 1.0).abs() < 1e-5);
+        assert!((radiance.r() - 8.0 / 17.0).abs() < 1e-5);
+        assert!((spot_radiance.r() - radiance.r()).abs() < 1e-5);
+
+        // Outside of the cone, the spotlight is dark.
+        assert!(spot.sample(Point::new(4.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn spot_light_profile_follows_its_c0_plane() {
+        // A profile that is bright in the C0 plane and dark across it.
+        let profile = IesProfile::parse(
+            "TILT=NONE
+1 1000 1 2 2 1 2 0.5 1.2 0
+1.0 1.0 50
+0 60
+0 90
+100 100
+0 0
+",
+        )
+        .unwrap();
+        let position = Point::new(0.0, 4.0, 0.0);
+        let target = Point::new(0.0, 0.0, 0.0);
+        let intensity = Color::new(8.0, 8.0, 8.0);
+        let spot = |c0: Vec3| {
+            SpotLight::new(position, target, intensity, 60.0, 60.0)
+                .with_profile(Arc::new(profile.clone()), c0)
+        };
+        let along_x = Point::new(4.0, 0.0, 0.0);
+        let along_z = Point::new(0.0, 0.0, 4.0);
+        let x = spot(Vec3::new(1.0, 0.0, 0.0));
+        assert!(x.sample(along_x).is_some());
+        assert!(x.sample(along_z).is_none());
+        let z = spot(Vec3::new(0.0, 0.0, 1.0));
+        assert!(z.sample(along_x).is_none());
+        assert!(z.sample(along_z).is_some());
+    }
+}
@@ -0,0 +1,467 @@
+//! This module contains a bounding volume hierarchy over [Light]s, which
+//! picks one of many lights at a point with a probability proportional to an
+//! estimate of its contribution. See [LightBVH].
+//!
+//! The hierarchy follows Conty Estevez and Kulla, "Importance Sampling of
+//! Many Lights with Adaptive Tree Splitting", as implemented in pbrt-v4.
+
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    light::Light,
+    point::Point,
+    random_0_1_f32,
+    vec3::{Unit3, Vec3},
+    PI,
+};
+
+#[derive(Debug, Clone, Copy)]
+/// A conservative summary of where a light is and into which directions it
+/// emits, from which the importance of the light at a point is estimated.
+///
+/// The directions are bounded by a cone of normals around an axis, which is
+/// widened by the spread of the emission around each normal. E.g., a
+/// one-sided quad has a cone of normals with an angle of 0 and an emission
+/// spread of 90 degrees, while a point light has a cone of all directions.
+pub struct LightBounds {
+    bounds: AABB,
+    /// The unit axis of the cone of normals.
+    axis: Unit3,
+    /// The cosine of the angle of the cone of normals.
+    cos_theta_o: f32,
+    /// The cosine of the angle of the emission around each normal.
+    cos_theta_e: f32,
+    /// The total power of the light, e.g., its luminance times its area
+    /// times `PI` for a diffuse area light.
+    power: f32,
+    /// Whether the light also emits along the negated normals.
+    two_sided: bool,
+}
+
+impl LightBounds {
+    /// Create new bounds of a light.
+    ///
+    /// * `bounds` - The bounding box of the light.
+    /// * `axis` - The axis of the cone of normals of the light.
+    /// * `cos_theta_o` - The cosine of the angle of the cone of normals, i.e.,
+    ///   1 for a flat light and -1 for all directions.
+    /// * `cos_theta_e` - The cosine of the angle by which the light spreads
+    ///   around each normal, e.g., 0 for a diffuse emitter.
+    /// * `power` - An estimate of the total power of the light.
+    /// * `two_sided` - Whether the light also emits along the negated normals.
+    pub fn new(
+        bounds: AABB,
+        axis: Vec3,
+        cos_theta_o: f32,
+        cos_theta_e: f32,
+        power: f32,
+        two_sided: bool,
+    ) -> Self {
+        Self {
+            bounds,
+            axis: axis.unit(),
+            cos_theta_o,
+            cos_theta_e,
+            power,
+            two_sided,
+        }
+    }
+
+    /// The total power of the light.
+    pub fn power(&self) -> f32 {
+        self.power
+    }
+
+    /// Create bounds that contain both `a` and `b`.
+    fn union(a: &Self, b: &Self) -> Self {
+        let (axis, cos_theta_o) = Self::cone_union(a.axis, a.cos_theta_o, b.axis, b.cos_theta_o);
+        Self {
+            bounds: AABB::from_aabbs(&a.bounds, &b.bounds),
+            axis,
+            cos_theta_o,
+            cos_theta_e: a.cos_theta_e.min(b.cos_theta_e),
+            power: a.power + b.power,
+            two_sided: a.two_sided || b.two_sided,
+        }
+    }
+
+    /// The smallest cone that contains the cones around `axis_a` and
+    /// `axis_b` with the cosines of their angles `cos_a` and `cos_b`.
+    fn cone_union(axis_a: Unit3, cos_a: f32, axis_b: Unit3, cos_b: f32) -> (Unit3, f32) {
+        let theta_a = cos_a.clamp(-1.0, 1.0).acos();
+        let theta_b = cos_b.clamp(-1.0, 1.0).acos();
+        let theta_d = axis_a.dot(*axis_b).clamp(-1.0, 1.0).acos();
+        if (theta_d + theta_b).min(PI) <= theta_a {
+            return (axis_a, cos_a);
+        }
+        if (theta_d + theta_a).min(PI) <= theta_b {
+            return (axis_b, cos_b);
+        }
+
+        // Rotate the axis of `a` towards the axis of `b` until the new cone
+        // touches the far sides of both cones.
+        let theta_o = 0.5 * (theta_a + theta_d + theta_b);
+        let rotation_axis = axis_a.cross(*axis_b);
+        if theta_o >= PI || rotation_axis.near_zero() {
+            return (axis_a, -1.0);
+        }
+        let theta_r = theta_o - theta_a;
+        let rotation_axis = rotation_axis.unit();
+        let axis = theta_r.cos() * *axis_a + theta_r.sin() * rotation_axis.cross(*axis_a);
+        (axis.unit(), theta_o.cos())
+    }
+
+    /// Estimate the contribution of the light at `p`. The estimate is 0 only
+    /// if no light of these bounds can arrive at `p`.
+    fn importance(&self, p: Point) -> f32 {
+        if self.power <= 0.0 {
+            return 0.0;
+        }
+
+        // Do not let the estimate grow without bounds close to the light.
+        let center = self.bounds.center();
+        let radius_squared = 0.25 * self.bounds.diagonal().length_squared();
+        let distance_squared = (p - center).length_squared();
+        let clamped_distance_squared = distance_squared.max(radius_squared);
+
+        // The angle between the axis and the direction towards `p`.
+        let to_point = (p - center).unit();
+        let mut cos_theta_w = self.axis.dot(*to_point);
+        if self.two_sided {
+            cos_theta_w = cos_theta_w.abs();
+        }
+        let sin_theta_w = (1.0 - cos_theta_w * cos_theta_w).max(0.0).sqrt();
+
+        // The angle of a cone from `p` that contains the bounding sphere.
+        let (sin_theta_b, cos_theta_b) = if distance_squared < radius_squared {
+            (0.0, -1.0)
+        } else {
+            let sin_squared = radius_squared / distance_squared;
+            (sin_squared.sqrt(), (1.0 - sin_squared).max(0.0).sqrt())
+        };
+
+        // The smallest angle between the direction towards `p` and any
+        // normal, i.e., `theta_w - theta_o - theta_b` clamped at 0.
+        let sin_theta_o = (1.0 - self.cos_theta_o * self.cos_theta_o).max(0.0).sqrt();
+        let (sin_theta_x, cos_theta_x) =
+            Self::sub_clamped(sin_theta_w, cos_theta_w, sin_theta_o, self.cos_theta_o);
+        let (_, cos_theta_p) =
+            Self::sub_clamped(sin_theta_x, cos_theta_x, sin_theta_b, cos_theta_b);
+        if cos_theta_p < self.cos_theta_e {
+            return 0.0;
+        }
+        self.power * cos_theta_p / clamped_distance_squared
+    }
+
+    /// The sine and cosine of the difference of the angles `a` and `b`,
+    /// which is clamped at 0.
+    fn sub_clamped(sin_a: f32, cos_a: f32, sin_b: f32, cos_b: f32) -> (f32, f32) {
+        if cos_a > cos_b {
+            return (0.0, 1.0);
+        }
+        (
+            (sin_a * cos_b - cos_a * sin_b).max(0.0),
+            cos_a * cos_b + sin_a * sin_b,
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A node of the hierarchy, which is either a single light or splits the
+/// lights into two children.
+enum Node {
+    Leaf {
+        light: Arc<dyn Light>,
+        bounds: LightBounds,
+    },
+    Interior {
+        bounds: LightBounds,
+        children: Box<[Node; 2]>,
+    },
+}
+
+impl Node {
+    /// Create a new node from a slice of lights and their bounds. The slice
+    /// needs to be mutable since this constructor sorts the lights.
+    fn new(lights: &mut [(Arc<dyn Light>, LightBounds)]) -> Self {
+        if let [(light, bounds)] = lights {
+            return Self::Leaf {
+                light: light.clone(),
+                bounds: *bounds,
+            };
+        }
+
+        // Split the lights in half along the longest axis of their centers.
+        let centers = lights.iter().fold(AABB::empty(), |centers, (_, bounds)| {
+            let center = bounds.bounds.center();
+            AABB::from_aabbs(&centers, &AABB::from_points(center, center))
+        });
+        let dimension = centers.longest_axis();
+        lights.sort_by(|(_, a), (_, b)| {
+            a.bounds.center()[dimension].total_cmp(&b.bounds.center()[dimension])
+        });
+        let (lower, upper) = lights.split_at_mut(lights.len() / 2);
+        let left = Self::new(lower);
+        let right = Self::new(upper);
+        Self::Interior {
+            bounds: LightBounds::union(left.bounds(), right.bounds()),
+            children: Box::new([left, right]),
+        }
+    }
+
+    fn bounds(&self) -> &LightBounds {
+        match self {
+            Self::Leaf { bounds, .. } | Self::Interior { bounds, .. } => bounds,
+        }
+    }
+
+    /// The probabilities of picking the children at `p`, or [None] if no
+    /// light of either child arrives at `p`.
+    fn split(left: &Self, right: &Self, p: Point) -> Option<[f32; 2]> {
+        let left = left.bounds().importance(p);
+        let right = right.bounds().importance(p);
+        let total = left + right;
+        if total <= 0.0 {
+            return None;
+        }
+        Some([left / total, right / total])
+    }
+
+    /// The density of picking a light at `p` that is hit at `hit` in
+    /// `direction`, given that this node is reached with `probability`.
+    fn pdf(&self, p: Point, direction: Vec3, hit: Point, probability: f32) -> f32 {
+        if !self.bounds().bounds.contains(hit) {
+            return 0.0;
+        }
+        match self {
+            Self::Leaf { light, .. } if light.contains(hit) => {
+                probability * light.pdf(p, direction)
+            }
+            Self::Leaf { .. } => 0.0,
+            Self::Interior { children, .. } => {
+                let [left, right] = children.as_ref();
+                let Some([p_left, p_right]) = Self::split(left, right, p) else {
+                    return 0.0;
+                };
+                left.pdf(p, direction, hit, probability * p_left)
+                    + right.pdf(p, direction, hit, probability * p_right)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+/// A hierarchy of lights, which picks one light at a point with a probability
+/// proportional to an estimate of its contribution, instead of sampling every
+/// light at every hit.
+///
+/// Each node of the hierarchy bounds the positions of its lights by a box and
+/// their directions of emission by a cone. A light is picked by walking down
+/// from the root, choosing a child by the power of its lights, their
+/// distance, and whether they face the point.
+///
+/// Lights without [Light::bounds], e.g., lights that are infinitely far
+/// away, are not part of the hierarchy and are sampled at every hit.
+pub struct LightBVH {
+    root: Option<Node>,
+    unbounded: Vec<Arc<dyn Light>>,
+}
+
+impl LightBVH {
+    /// Create a new hierarchy of `lights`. Lights whose power is estimated to
+    /// be 0 are never picked and are only found by rays that hit them.
+    pub fn new(lights: &[Arc<dyn Light>]) -> Self {
+        let mut bounded = Vec::new();
+        let mut unbounded = Vec::new();
+        for light in lights {
+            match light.bounds() {
+                Some(bounds) if bounds.power() > 0.0 => bounded.push((light.clone(), bounds)),
+                Some(_) => {}
+                None => unbounded.push(light.clone()),
+            }
+        }
+        let root = (!bounded.is_empty()).then(|| Node::new(&mut bounded));
+        Self { root, unbounded }
+    }
+
+    /// The lights that are not part of the hierarchy.
+    pub fn unbounded(&self) -> &[Arc<dyn Light>] {
+        &self.unbounded
+    }
+
+    /// Pick a light of the hierarchy for the point `p`. Returns the light
+    /// and the probability with which it was picked, or [None] if no light
+    /// arrives at `p`.
+    pub fn sample(&self, p: Point) -> Option<(&dyn Light, f32)> {
+        let mut node = self.root.as_ref()?;
+        let mut probability = 1.0;
+        loop {
+            match node {
+                Node::Leaf { light, bounds } => {
+                    if bounds.importance(p) <= 0.0 {
+                        return None;
+                    }
+                    return Some((light.as_ref(), probability));
+                }
+                Node::Interior { children, .. } => {
+                    let [left, right] = children.as_ref();
+                    let [p_left, p_right] = Node::split(left, right, p)?;
+                    if random_0_1_f32() < p_left {
+                        node = left;
+                        probability *= p_left;
+                    } else {
+                        node = right;
+                        probability *= p_right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The probability density with respect to the solid angle at `p` that
+    /// [LightBVH::sample] picks the light that is hit at `hit` in `direction`
+    /// and that the light samples `direction`. The light is found by the
+    /// bounds that contain `hit` and by [Light::contains].
+    pub fn pdf(&self, p: Point, direction: Vec3, hit: Point) -> f32 {
+        let Some(root) = &self.root else {
+            return 0.0;
+        };
+        if let Node::Leaf { light, bounds } = root {
+            let hit_light = bounds.bounds.contains(hit) && light.contains(hit);
+            if bounds.importance(p) <= 0.0 || !hit_light {
+                return 0.0;
+            }
+            return light.pdf(p, direction);
+        }
+        root.pdf(p, direction, hit, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        color::Color, hittable::Sphere, light::Light, material::DiffuseLight, point::Point,
+        quad::Quad, texture::Texture, vec3::Vec3,
+    };
+
+    use super::LightBVH;
+
+    #[derive(Debug)]
+    /// A texture that is only lit in a stripe along its left edge, so that
+    /// it is dark in the middle.
+    struct Stripe;
+
+    impl Texture for Stripe {
+        fn value(&self, u: f32, _v: f32, _p: Point) -> Color {
+            if u < 0.25 {
+                Color::white()
+            } else {
+                Color::black()
+            }
+        }
+    }
+
+    #[test]
+    fn picks_lights_by_their_contribution() {
+        let light = Arc::new(DiffuseLight::from_color(Color::new(4.0, 4.0, 4.0)).one_sided());
+        let quad = |x: f32| {
+            Arc::new(Quad::new(
+                Point::new(x, 2.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 1.0),
+                light.clone(),
+            ))
+        };
+        // Two lights face down. The third one faces up, away from the point.
+        let near = quad(0.0);
+        let far = quad(20.0);
+        let away = Arc::new(Quad::new(
+            Point::new(0.0, 3.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            light.clone(),
+        ));
+        let lights: Vec<Arc<dyn Light>> = vec![near.clone(), far.clone(), away];
+        let bvh = LightBVH::new(&lights);
+
+        let p = Point::new(0.5, 0.0, 0.5);
+        let n = 10000;
+        let mut near_count = 0;
+        for _ in 0..n {
+            let (light, probability) = bvh.sample(p).unwrap();
+            let (direction, distance, _) = light.sample(p).unwrap();
+            let hit = p + distance * direction;
+            let pdf = bvh.pdf(p, direction, hit);
+            assert!((pdf - probability * light.pdf(p, direction)).abs() < 1e-3 * pdf);
+            if probability > 0.5 {
+                near_count += 1;
+            }
+        }
+
+        // The near light is picked most of the time, the light that faces
+        // away is never picked.
+        assert!(near_count > n * 9 / 10);
+        let to_far = Point::new(20.5, 2.0, 0.5) - p;
+        let far_pdf = bvh.pdf(p, *to_far.unit(), Point::new(20.5, 2.0, 0.5));
+        assert!(far_pdf > 0.0 && far_pdf < far.pdf(p, to_far) * 0.1);
+        assert_eq!(
+            bvh.pdf(p, Vec3::new(0.0, 1.0, 0.0), Point::new(0.5, 3.0, 0.5)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn weighs_only_the_light_that_is_hit() {
+        let light = Arc::new(DiffuseLight::from_color(Color::new(4.0, 4.0, 4.0)).one_sided());
+        // A light faces down, and a tilted light overlaps its bounds.
+        let flat = Arc::new(Quad::new(
+            Point::new(0.0, 2.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            light.clone(),
+        ));
+        let tilted = Arc::new(Quad::new(
+            Point::new(0.0, 1.5, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            light,
+        ));
+        let lights: Vec<Arc<dyn Light>> = vec![flat.clone(), tilted];
+        let bvh = LightBVH::new(&lights);
+
+        let p = Point::new(0.5, 0.0, 0.5);
+        let probability = std::iter::repeat_with(|| bvh.sample(p).unwrap())
+            .find(|(light, _)| std::ptr::addr_eq(*light, flat.as_ref()))
+            .map(|(_, probability)| probability)
+            .unwrap();
+        assert!(probability < 1.0);
+
+        // The hit lies in the bounds of both lights, but only on the flat one.
+        let hit = Point::new(0.25, 2.0, 0.5);
+        let direction = hit - p;
+        let pdf = bvh.pdf(p, direction, hit);
+        let expected = probability * flat.pdf(p, direction);
+        assert!((pdf - expected).abs() < 1e-3 * expected);
+    }
+
+    #[test]
+    fn picks_lights_that_are_dark_in_the_middle() {
+        let light = Arc::new(DiffuseLight::new(Arc::new(Stripe)));
+        let quad = Quad::new(
+            Point::new(0.0, 2.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            light.clone(),
+        );
+        let sphere = Sphere::new(Point::new(0.0, 2.0, 0.0), 0.5, light);
+        let lights: [Arc<dyn Light>; 2] = [Arc::new(quad), Arc::new(sphere)];
+        for light in lights {
+            assert!(light.bounds().unwrap().power() > 0.0);
+            let bvh = LightBVH::new(&[light]);
+            assert!(bvh.sample(Point::new(0.5, 0.0, 0.5)).is_some());
+        }
+    }
+}
@@ -2,16 +2,23 @@
 //! [Ray] is refracted by a surface. The module also contains the implementations
 //! for types that implement the material trait.
 
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, path::Path, sync::Arc};
 
 use crate::{
     color::Color,
+    degrees_to_radians,
     hittable::HitRecord,
+    ies::IesProfile,
+    microfacet::{self, fresnel_complex, fresnel_dielectric, TrowbridgeReitz},
+    onb::ONB,
     point::Point,
-    random_0_1_f32, random_unit_vector,
+    random_0_1_f32, random_cosine_direction, random_unit_vector,
     ray::Ray,
-    texture::{SolidColor, Texture},
+    spectrum::sample_visible_wavelength,
+    texture::{ImageTexture, SolidColor, Texture},
+    thin_film::{Substrate, ThinFilm},
     vec3::Vec3,
+    PI,
 };
 
 fn reflect(v: Vec3, n: Vec3) -> Vec3 {
@@ -25,6 +32,32 @@ fn refract(uv: Vec3, n: Vec3, eta_i_over_eta_t: f32) -> Vec3 {
     r_out_perp + r_out_parallel
 }
 
+/// Compute the absorption coefficient such that light keeps the fraction
+/// `color` of its intensity after travelling `distance`.
+fn absorption_from_color(color: Color, distance: f32) -> Color {
+    let coefficient = |c: f32| -c.max(1e-6).ln() / distance;
+    Color::new(
+        coefficient(color.r()),
+        coefficient(color.g()),
+        coefficient(color.b()),
+    )
+}
+
+/// Compute the fraction of light that is not absorbed by a medium with the
+/// given `absorption` coefficient on its way along `ray` to the hit. Rays
+/// which hit the back face of a surface travelled inside of the medium.
+fn transmittance(absorption: Color, ray: &Ray, hit_record: &HitRecord) -> Color {
+    if hit_record.front_face() {
+        return Color::white();
+    }
+    let distance = (hit_record.p() - *ray.origin()).length();
+    Color::new(
+        (-absorption.r() * distance).exp(),
+        (-absorption.g() * distance).exp(),
+        (-absorption.b() * distance).exp(),
+    )
+}
+
 /// A trait that defines behavior that structs which can act as the surface
 /// material of objects in the world must implement.
 ///
@@ -38,10 +71,49 @@ pub trait Material: Debug + Send + Sync {
     /// its attenuation as a [Color].
     fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)>;
 
-    /// Returns the color of the light this material emits.
-    fn emitted(&self, _u: f32, _v: f32, _p: Point) -> Color {
+    /// Returns the color of the light this material emits towards the origin
+    /// of `ray`, which hit the surface as described by `hit_record`.
+    fn emitted(&self, _ray: &Ray, _hit_record: &HitRecord) -> Color {
+        Color::black()
+    }
+
+    /// Evaluate the fraction of light arriving from `direction` that is
+    /// scattered towards the origin of `ray`, i.e., the scattering function
+    /// times the cosine of `direction` to the normal. This is used to sample
+    /// lights explicitly, see [Light](crate::light::Light).
+    ///
+    /// Materials that only scatter into single directions, like mirrors and
+    /// smooth glass, return black, and so does the default implementation.
+    fn eval(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vec3) -> Color {
         Color::black()
     }
+
+    /// The probability density with respect to the solid angle that
+    /// [Material::scatter] picks `direction`. It is used to weigh explicitly
+    /// sampled light against light that scattered rays find on their own.
+    ///
+    /// The default implementation returns 0 for materials whose density is
+    /// unknown, which only find lights with a density, e.g., an
+    /// [EnvironmentMap](crate::environment::EnvironmentMap) or a
+    /// [SunLight](crate::light::SunLight), through scattered rays. Thus,
+    /// materials which implement [Material::eval] should implement this too.
+    fn pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vec3) -> f32 {
+        0.0
+    }
+
+    /// Returns the opacity of the material in `[0, 1]`. Rays pass through
+    /// the surface with the probability `1 - opacity` as if it were not
+    /// there, which cuts out the transparent parts of a surface.
+    fn opacity(&self, _u: f32, _v: f32, _p: Point) -> f32 {
+        1.0
+    }
+
+    /// Whether the direction or the attenuation of scattered rays depends on
+    /// their wavelength. Paths that scatter at such a material can only carry
+    /// a single wavelength in spectral mode.
+    fn is_dispersive(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -75,19 +147,125 @@ impl Material for Lambertian {
             scatter_direction
         };
 
-        let scattered = Ray::new(hit_record.p(), scatter_direction, ray.time());
+        let scattered = ray.spawn(hit_record.p(), scatter_direction);
         let attenuation = self
             .texture
             .value(hit_record.u(), hit_record.v(), hit_record.p());
         Some((scattered, attenuation))
     }
+
+    fn eval(&self, _ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let cos_theta = direction.unit().dot(*hit_record.normal());
+        if cos_theta <= 0.0 {
+            return Color::black();
+        }
+        let albedo = self
+            .texture
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        albedo * (cos_theta / PI)
+    }
+
+    fn pdf(&self, _ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        // Adding a random unit vector to the normal samples the cosine.
+        direction.unit().dot(*hit_record.normal()).max(0.0) / PI
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
+/// A rough diffuse material according to the Oren-Nayar model. Its surface
+/// consists of tiny lambertian V-shaped cavities, which reflect more light
+/// back towards its source than a lambertian surface does. This makes rough
+/// materials like clay, plaster, or the moon look flatter.
+pub struct OrenNayar {
+    texture: Arc<dyn Texture>,
+    /// The `A` coefficient of the model, which only depends on `sigma`.
+    a: f32,
+    /// The `B` coefficient of the model, which only depends on `sigma`.
+    b: f32,
+}
+
+impl OrenNayar {
+    /// Create a new Oren-Nayar material from its color and `sigma`, the
+    /// standard deviation of the slope angle of the cavities in degrees. A
+    /// `sigma` of 0 is a lambertian material.
+    pub fn new(albedo: Color, sigma: f32) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)), sigma)
+    }
+
+    /// Create a new Oren-Nayar material from a texture and `sigma` in
+    /// degrees, see [OrenNayar::new].
+    pub fn from_texture(texture: Arc<dyn Texture>, sigma: f32) -> Self {
+        let sigma = degrees_to_radians(sigma);
+        let sigma2 = sigma * sigma;
+        Self {
+            texture,
+            a: 1.0 - sigma2 / (2.0 * (sigma2 + 0.33)),
+            b: 0.45 * sigma2 / (sigma2 + 0.09),
+        }
+    }
+
+    /// The reflectance of the model from `wo` into `wi` in the local shading
+    /// frame relative to the albedo over `PI`.
+    fn reflectance(&self, wo: Vec3, wi: Vec3) -> f32 {
+        // The cosine of the azimuthal angle between both directions, and the
+        // sine of the larger and the tangent of the smaller polar angle.
+        let sin_theta_i = (1.0 - wi.z() * wi.z()).max(0.0).sqrt();
+        let sin_theta_o = (1.0 - wo.z() * wo.z()).max(0.0).sqrt();
+        let max_cos = if sin_theta_i > 1e-4 && sin_theta_o > 1e-4 {
+            ((wi.x() * wo.x() + wi.y() * wo.y()) / (sin_theta_i * sin_theta_o)).max(0.0)
+        } else {
+            0.0
+        };
+        let (sin_alpha, tan_beta) = if wi.z().abs() > wo.z().abs() {
+            (sin_theta_o, sin_theta_i / wi.z().abs())
+        } else {
+            (sin_theta_i, sin_theta_o / wo.z().abs().max(1e-4))
+        };
+        self.a + self.b * max_cos * sin_alpha * tan_beta
+    }
+}
+
+impl Material for OrenNayar {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        let frame = ONB::new(hit_record.normal());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = random_cosine_direction();
+
+        // Cosine sampling cancels the cosine and 1 / PI of the BRDF.
+        let scattered = ray.spawn(hit_record.p(), frame.to_world(wi));
+        let albedo = self
+            .texture
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        let attenuation = albedo * self.reflectance(wo, wi);
+        Some((scattered, attenuation))
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let frame = ONB::new(hit_record.normal());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        if wi.z() <= 0.0 {
+            return Color::black();
+        }
+        let albedo = self
+            .texture
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        albedo * (self.reflectance(wo, wi) * wi.z() / PI)
+    }
+
+    fn pdf(&self, _ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        direction.unit().dot(*hit_record.normal()).max(0.0) / PI
+    }
+}
+
+#[derive(Clone, Debug)]
 /// A material that implements reflection by a metal material.
 pub struct Metal {
     albedo: Color,
     fuzz: f32,
+    /// The microfacets of a brushed metal, which replace the fuzz.
+    distribution: Option<TrowbridgeReitz>,
+    thin_film: Option<ThinFilm>,
 }
 
 impl Metal {
@@ -95,31 +273,383 @@ impl Metal {
     /// the reflection. A bigger `fuzz` means more deviation from the true
     /// reflection.
     pub fn new(albedo: Color, fuzz: f32) -> Self {
-        Self { albedo, fuzz }
+        Self {
+            albedo,
+            fuzz,
+            distribution: None,
+            thin_film: None,
+        }
+    }
+
+    /// Create a new brushed metal whose roughness differs along the tangent
+    /// of the surface and perpendicular to it. For a metal that is brushed
+    /// along the tangent, `roughness_x` is the smaller one. The highlights
+    /// are stretched along the rougher direction, i.e., across the brushing.
+    /// The tangent follows the texture coordinate `u`, see
+    /// [HitRecord::tangent].
+    ///
+    /// * `albedo` - The color of the metal.
+    /// * `roughness_x` - The roughness along the tangent in `[0, 1]`.
+    /// * `roughness_y` - The roughness perpendicular to the tangent in
+    ///   `[0, 1]`.
+    pub fn anisotropic(albedo: Color, roughness_x: f32, roughness_y: f32) -> Self {
+        Self {
+            albedo,
+            fuzz: 0.0,
+            distribution: Some(TrowbridgeReitz::from_anisotropic_roughness(
+                roughness_x,
+                roughness_y,
+            )),
+            thin_film: None,
+        }
+    }
+
+    /// Coat the metal with a thin film, e.g., oil or an oxide layer, which
+    /// makes its reflection iridescent.
+    pub fn with_thin_film(mut self, thin_film: ThinFilm) -> Self {
+        self.thin_film = Some(thin_film);
+        self
+    }
+
+    /// The fraction of light reflected at the angle `cos_theta` to the
+    /// (microfacet) normal.
+    fn reflectance(&self, ray: &Ray, hit_record: &HitRecord, cos_theta: f32) -> Color {
+        match &self.thin_film {
+            Some(thin_film) => {
+                let substrate = Substrate::Mirror(self.albedo);
+                thin_film.reflectance(hit_record, cos_theta, 1.0, substrate, ray.wavelength())
+            }
+            None => self.albedo,
+        }
     }
 }
 
 impl Material for Metal {
     fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
-        let reflected = reflect(*ray.direction(), *hit_record.normal());
-        let reflected = *reflected.unit() + (self.fuzz * *random_unit_vector());
-        let scattered = Ray::new(hit_record.p(), reflected, ray.time());
-        Some((scattered, self.albedo))
+        // The reflected direction, the cosine between the incoming light and
+        // the (microfacet) normal, and the fraction of light that is not
+        // shadowed by other microfacets.
+        let (reflected, cos_theta, masking) = match &self.distribution {
+            Some(distribution) => {
+                let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+                let wo = frame.to_local(-*ray.direction().unit());
+                if wo.z() <= 0.0 {
+                    return None;
+                }
+                let wm = distribution.sample_wm(wo);
+                let wi = microfacet::reflect(wo, wm);
+                if wi.z() <= 0.0 {
+                    return None;
+                }
+                let masking = distribution.g(wo, wi) / distribution.g1(wo);
+                (frame.to_world(wi), wo.dot(wm), masking)
+            }
+            None => {
+                let reflected = reflect(*ray.direction(), *hit_record.normal());
+                let reflected = *reflected.unit() + (self.fuzz * *random_unit_vector());
+                let cos_theta = -ray.direction().unit().dot(*hit_record.normal());
+                (reflected, cos_theta, 1.0)
+            }
+        };
+        let scattered = ray.spawn(hit_record.p(), reflected);
+        let attenuation = self.reflectance(ray, &hit_record, cos_theta);
+        Some((scattered, attenuation * masking))
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let Some(distribution) = &self.distribution else {
+            return Color::black();
+        };
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        match distribution.eval_reflection(wo, wi) {
+            Some((wm, value)) => self.reflectance(ray, hit_record, wo.dot(wm)) * value,
+            None => Color::black(),
+        }
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let Some(distribution) = &self.distribution else {
+            return 0.0;
+        };
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        distribution.pdf_reflection(wo, wi)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.thin_film.is_some()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// A physically based conductor (metal). Its surface consists of microfacets
+/// distributed according to the Trowbridge-Reitz (GGX) distribution, which
+/// are sampled proportional to their visibility. The color of the reflection
+/// stems from the Fresnel equations for the complex refractive index
+/// `eta + i * k` of the metal, given for the red, green, and blue channel.
+pub struct Conductor {
+    eta: Color,
+    k: Color,
+    distribution: TrowbridgeReitz,
+}
+
+impl Conductor {
+    /// Create a new conductor.
+    ///
+    /// * `eta` - The real part of the refractive index per color channel.
+    /// * `k` - The imaginary part of the refractive index (the absorption
+    ///   coefficient) per color channel.
+    /// * `roughness` - The roughness of the surface in `[0, 1]`, where 0 is a
+    ///   perfect mirror.
+    pub fn new(eta: Color, k: Color, roughness: f32) -> Self {
+        Self {
+            eta,
+            k,
+            distribution: TrowbridgeReitz::from_roughness(roughness),
+        }
+    }
+
+    /// Replace the roughness of the conductor by a roughness along the
+    /// tangent of the surface and one perpendicular to it, e.g., for brushed
+    /// aluminium. See [Metal::anisotropic].
+    pub fn with_anisotropic_roughness(mut self, roughness_x: f32, roughness_y: f32) -> Self {
+        self.distribution = TrowbridgeReitz::from_anisotropic_roughness(roughness_x, roughness_y);
+        self
+    }
+
+    /// Create a new gold conductor with the given `roughness`.
+    pub fn gold(roughness: f32) -> Self {
+        Self::new(
+            Color::new(0.143, 0.374, 1.442),
+            Color::new(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+
+    /// Create a new copper conductor with the given `roughness`.
+    pub fn copper(roughness: f32) -> Self {
+        Self::new(
+            Color::new(0.200, 0.924, 1.102),
+            Color::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    /// Create a new aluminium conductor with the given `roughness`.
+    pub fn aluminium(roughness: f32) -> Self {
+        Self::new(
+            Color::new(1.657, 0.880, 0.521),
+            Color::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+
+    /// Create a new silver conductor with the given `roughness`.
+    pub fn silver(roughness: f32) -> Self {
+        Self::new(
+            Color::new(0.155, 0.117, 0.138),
+            Color::new(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    /// The Fresnel reflectance per color channel for the given cosine between
+    /// the incoming light and the (microfacet) normal.
+    fn fresnel(&self, cos_theta: f32) -> Color {
+        Color::new(
+            fresnel_complex(cos_theta, self.eta.r(), self.k.r()),
+            fresnel_complex(cos_theta, self.eta.g(), self.k.g()),
+            fresnel_complex(cos_theta, self.eta.b(), self.k.b()),
+        )
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let (wi, attenuation) = if self.distribution.effectively_smooth() {
+            let wi = Vec3::new(-wo.x(), -wo.y(), wo.z());
+            (wi, self.fresnel(wo.z()))
+        } else {
+            // Sample a visible microfacet and reflect about it. The weight of
+            // the sample is f * cos(theta_i) / pdf = F * G / G1.
+            let wm = self.distribution.sample_wm(wo);
+            let wi = microfacet::reflect(wo, wm);
+            if wi.z() <= 0.0 {
+                return None;
+            }
+            let masking = self.distribution.g(wo, wi) / self.distribution.g1(wo);
+            (wi, self.fresnel(wo.dot(wm)) * masking)
+        };
+
+        let scattered = ray.spawn(hit_record.p(), frame.to_world(wi));
+        Some((scattered, attenuation))
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        match self.distribution.eval_reflection(wo, wi) {
+            Some((wm, value)) => self.fresnel(wo.dot(wm)) * value,
+            None => Color::black(),
+        }
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        self.distribution.pdf_reflection(wo, wi)
     }
 }
 
 #[derive(Clone, Copy, Debug)]
+/// The refractive index of a dielectric, which may depend on the wavelength
+/// of light. A refractive index that varies with the wavelength splits white
+/// light into its colors, which is called dispersion.
+pub enum RefractiveIndex {
+    /// The same refractive index for all wavelengths.
+    Constant(f32),
+    /// Cauchy's equation `n = a + b / λ²` with the wavelength `λ` in
+    /// micrometers.
+    Cauchy {
+        /// The refractive index at very long wavelengths.
+        a: f32,
+        /// The strength of the dispersion in square micrometers.
+        b: f32,
+    },
+    /// The Sellmeier equation `n² = 1 + Σ b_i λ² / (λ² - c_i)` with the
+    /// wavelength `λ` in micrometers.
+    Sellmeier {
+        /// The strengths of the three absorption resonances.
+        b: [f32; 3],
+        /// The squared wavelengths of the three absorption resonances in
+        /// square micrometers.
+        c: [f32; 3],
+    },
+}
+
+impl RefractiveIndex {
+    /// The borosilicate crown glass BK7, which is commonly used for lenses.
+    pub fn bk7() -> Self {
+        Self::Sellmeier {
+            b: [1.039_612, 0.231_792_34, 1.010_469_5],
+            c: [0.006_000_699, 0.020_017_914, 103.560_65],
+        }
+    }
+
+    /// Fused silica, i.e., quartz glass.
+    pub fn fused_silica() -> Self {
+        Self::Sellmeier {
+            b: [0.696_166_3, 0.407_942_6, 0.897_479_4],
+            c: [0.004_679_148, 0.013_512_063, 97.934_003],
+        }
+    }
+
+    /// The dense flint glass SF11, which disperses light strongly.
+    pub fn dense_flint() -> Self {
+        Self::Sellmeier {
+            b: [1.737_597, 0.313_747_35, 1.898_781],
+            c: [0.013_188_707, 0.062_306_814, 155.236_3],
+        }
+    }
+
+    /// Diamond, which has a high refractive index and strong dispersion.
+    pub fn diamond() -> Self {
+        Self::Cauchy {
+            a: 2.385,
+            b: 0.0117,
+        }
+    }
+
+    /// Whether the refractive index depends on the wavelength.
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, Self::Constant(_))
+    }
+
+    /// Evaluate the refractive index at `wavelength` in nanometers. Without
+    /// a wavelength, the index at 550 nanometers is used, which is in the
+    /// middle of the visible spectrum.
+    pub fn at(&self, wavelength: Option<f32>) -> f32 {
+        let micrometers = wavelength.unwrap_or(550.0) / 1000.0;
+        let lambda2 = micrometers * micrometers;
+        match *self {
+            Self::Constant(n) => n,
+            Self::Cauchy { a, b } => a + b / lambda2,
+            Self::Sellmeier { b, c } => (1.0
+                + b.iter()
+                    .zip(c)
+                    .map(|(b, c)| b * lambda2 / (lambda2 - c))
+                    .sum::<f32>())
+            .sqrt(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 /// A material that implements reflection by a dieletric material.
 pub struct Dielectric {
     /// Refractive index in vacuum or air, or the ratio of the material's refractive index over
     /// the refractive index of the enclosing media
-    refraction_index: f32,
+    refraction_index: RefractiveIndex,
+    /// The absorption coefficient per color channel of the medium inside.
+    absorption: Color,
+    /// An optional thin film on the outside of the material.
+    thin_film: Option<ThinFilm>,
 }
 
 impl Dielectric {
     /// Create a new material with a given refraction index.
     pub fn new(refraction_index: f32) -> Self {
-        Self { refraction_index }
+        Self::dispersive(RefractiveIndex::Constant(refraction_index))
+    }
+
+    /// Create a new tinted material with a given refraction index, see
+    /// [Dielectric::with_tint].
+    pub fn tinted(refraction_index: f32, color: Color, distance: f32) -> Self {
+        Self::new(refraction_index).with_tint(color, distance)
+    }
+
+    /// Create a new material whose refraction index depends on the wavelength.
+    /// Rays that hit the material are assigned a random wavelength if they do
+    /// not carry one yet, which is refracted according to its own index.
+    pub fn dispersive(refraction_index: RefractiveIndex) -> Self {
+        Self {
+            refraction_index,
+            absorption: Color::black(),
+            thin_film: None,
+        }
+    }
+
+    /// Coat the material with a thin film. E.g., a soap bubble is a film of
+    /// water around air, i.e., a material with a refraction index of 1.0.
+    pub fn with_thin_film(mut self, thin_film: ThinFilm) -> Self {
+        self.thin_film = Some(thin_film);
+        self
+    }
+
+    /// Let the inside of the material absorb light. Light that travels the
+    /// distance `d` inside of the material is attenuated by
+    /// `exp(-absorption * d)` (Beer-Lambert law).
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Tint the material such that light that travels the given `distance`
+    /// inside of it keeps the fraction `color` of its intensity. E.g., glass
+    /// that looks green when it is 1 unit thick.
+    pub fn with_tint(self, color: Color, distance: f32) -> Self {
+        self.with_absorption(absorption_from_color(color, distance))
     }
 
     /// Schlick approximation for reflectance.
@@ -132,38 +662,754 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        let wavelength = if self.refraction_index.is_dispersive() {
+            ray.wavelength()
+                .or_else(|| Some(sample_visible_wavelength(random_0_1_f32())))
+        } else {
+            ray.wavelength()
+        };
+        let refraction_index = self.refraction_index.at(wavelength);
         let ri = if hit_record.front_face() {
-            1.0 / self.refraction_index
+            1.0 / refraction_index
         } else {
-            self.refraction_index
+            refraction_index
         };
         let unit_direction = ray.direction().unit();
         let cos_theta = -unit_direction.dot(*hit_record.normal()).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
         let cannot_refract = ri * sin_theta > 1.0;
 
-        let direction = if cannot_refract || Self::reflectance(cos_theta, ri) > random_0_1_f32() {
-            reflect(*unit_direction, *hit_record.normal())
+        // The probability of a reflection, and the attenuation of reflected
+        // and refracted light relative to their probability.
+        let (reflect_probability, reflected, refracted) = match &self.thin_film {
+            Some(thin_film) => {
+                let (eta_i, eta_t) = if hit_record.front_face() {
+                    (1.0, refraction_index)
+                } else {
+                    (refraction_index, 1.0)
+                };
+                let substrate = Substrate::Dielectric(eta_t);
+                let reflectance =
+                    thin_film.reflectance(&hit_record, cos_theta, eta_i, substrate, wavelength);
+                let p = (reflectance.r() + reflectance.g() + reflectance.b()) / 3.0;
+                (
+                    p,
+                    reflectance * (1.0 / p.max(f32::EPSILON)),
+                    (Color::white() - reflectance) * (1.0 / (1.0 - p).max(f32::EPSILON)),
+                )
+            }
+            None => (
+                Self::reflectance(cos_theta, ri),
+                Color::white(),
+                Color::white(),
+            ),
+        };
+
+        let (direction, attenuation) = if cannot_refract || reflect_probability > random_0_1_f32() {
+            (reflect(*unit_direction, *hit_record.normal()), reflected)
         } else {
-            refract(*unit_direction, *hit_record.normal(), ri)
+            (
+                refract(*unit_direction, *hit_record.normal(), ri),
+                refracted,
+            )
         };
 
-        let scattered = Ray::new(hit_record.p(), direction, ray.time());
-        let attenuation = Color::white();
+        let scattered = ray
+            .spawn(hit_record.p(), direction)
+            .with_wavelength(wavelength);
+        let attenuation = attenuation * transmittance(self.absorption, ray, &hit_record);
         Some((scattered, attenuation))
     }
+
+    fn is_dispersive(&self) -> bool {
+        self.refraction_index.is_dispersive() || self.thin_film.is_some()
+    }
 }
 
+#[derive(Clone, Copy, Debug)]
+/// A dielectric with a rough surface, e.g., frosted glass. Like [Conductor],
+/// its surface consists of microfacets distributed according to the
+/// Trowbridge-Reitz (GGX) distribution. Light is reflected or transmitted
+/// through a visible microfacet according to the exact Fresnel equations.
+pub struct RoughDielectric {
+    /// Refractive index in vacuum or air, or the ratio of the material's refractive index over
+    /// the refractive index of the enclosing media
+    refraction_index: f32,
+    distribution: TrowbridgeReitz,
+    /// The absorption coefficient per color channel of the medium inside.
+    absorption: Color,
+}
+
+impl RoughDielectric {
+    /// Create a new material with a given refraction index and a `roughness`
+    /// in `[0, 1]`, where 0 is perfectly smooth glass.
+    pub fn new(refraction_index: f32, roughness: f32) -> Self {
+        Self {
+            refraction_index,
+            distribution: TrowbridgeReitz::from_roughness(roughness),
+            absorption: Color::black(),
+        }
+    }
+
+    /// Create a new tinted material, see [Dielectric::with_tint].
+    pub fn tinted(refraction_index: f32, roughness: f32, color: Color, distance: f32) -> Self {
+        Self::new(refraction_index, roughness).with_tint(color, distance)
+    }
+
+    /// Let the inside of the material absorb light, see
+    /// [Dielectric::with_absorption].
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Tint the material, see [Dielectric::with_tint].
+    pub fn with_tint(self, color: Color, distance: f32) -> Self {
+        self.with_absorption(absorption_from_color(color, distance))
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        // The ratio of the refractive index on the other side of the surface
+        // over the one on the side of the ray.
+        let eta = if hit_record.front_face() {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        };
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let (wi, weight) = self.distribution.sample_dielectric(wo, eta)?;
+        let attenuation = transmittance(self.absorption, ray, &hit_record) * weight;
+        let scattered = ray.spawn(hit_record.p(), frame.to_world(wi));
+        Some((scattered, attenuation))
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        // Lights are seen both in the reflection and through the surface.
+        let eta = if hit_record.front_face() {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        };
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        transmittance(self.absorption, ray, hit_record)
+            * self.distribution.eval_dielectric(wo, wi, eta)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let eta = if hit_record.front_face() {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        };
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        self.distribution.pdf_dielectric(wo, wi, eta)
+    }
+}
+
+/// The maximum number of times light bounces between the coat and the base
+/// of a [Coated] material before it is absorbed.
+const MAX_COAT_BOUNCES: u32 = 16;
+
 #[derive(Debug, Clone)]
-/// A struct that implements a source of diffuse light.
+/// A material that is coated by a thin dielectric layer, e.g., varnished
+/// wood or car paint. Light is reflected or refracted at the top of the coat
+/// according to the Fresnel equations, refracted light travels through the
+/// coat to the base material, which scatters it, and may bounce between the
+/// base and the inside of the coat several times before it leaves.
+pub struct Coated {
+    /// The material below the coat.
+    base: Arc<dyn Material>,
+    /// The refractive index of the coat.
+    refraction_index: f32,
+    distribution: TrowbridgeReitz,
+    /// The absorption coefficient per color channel of the coat.
+    absorption: Color,
+    /// The thickness of the coat.
+    thickness: f32,
+}
+
+impl Coated {
+    /// Create a new clear coat over the `base` material.
+    ///
+    /// * `base` - The material below the coat.
+    /// * `refraction_index` - The refractive index of the coat.
+    /// * `roughness` - The roughness of the top of the coat in `[0, 1]`,
+    ///   where 0 is perfectly smooth.
+    pub fn new(base: Arc<dyn Material>, refraction_index: f32, roughness: f32) -> Self {
+        Self::with_absorption(base, refraction_index, roughness, Color::black(), 0.0)
+    }
+
+    /// Create a new coat over the `base` material that absorbs light, see
+    /// [Dielectric::with_absorption]. The longer light travels through the
+    /// coat of the given `thickness`, the more it is absorbed, which tints
+    /// the base more strongly at grazing angles.
+    pub fn with_absorption(
+        base: Arc<dyn Material>,
+        refraction_index: f32,
+        roughness: f32,
+        absorption: Color,
+        thickness: f32,
+    ) -> Self {
+        Self {
+            base,
+            refraction_index,
+            distribution: TrowbridgeReitz::from_roughness(roughness),
+            absorption,
+            thickness,
+        }
+    }
+
+    /// The fraction of light that passes through the coat in a direction
+    /// whose cosine to the normal is `cos_theta`.
+    fn coat_transmittance(&self, cos_theta: f32) -> Color {
+        let distance = self.thickness / cos_theta.abs().max(1e-4);
+        Color::new(
+            (-self.absorption.r() * distance).exp(),
+            (-self.absorption.g() * distance).exp(),
+            (-self.absorption.b() * distance).exp(),
+        )
+    }
+}
+
+impl Material for Coated {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        if wo.z() <= 0.0 || !hit_record.front_face() {
+            return self.base.scatter(ray, hit_record);
+        }
+
+        // Reflect at or refract through the top of the coat.
+        let (mut w, weight) = self
+            .distribution
+            .sample_dielectric(wo, self.refraction_index)?;
+        let mut attenuation = Color::white() * weight;
+        if w.z() > 0.0 {
+            let scattered = ray.spawn(hit_record.p(), frame.to_world(w));
+            return Some((scattered, attenuation));
+        }
+
+        // Follow the light through the coat until it leaves the top again.
+        for _ in 0..MAX_COAT_BOUNCES {
+            attenuation = attenuation * self.coat_transmittance(w.z());
+            let direction = frame.to_world(w);
+            let base_ray = ray.spawn(hit_record.p() - direction, direction);
+            let (scattered, base_attenuation) = self.base.scatter(&base_ray, hit_record.copy())?;
+            attenuation = attenuation * base_attenuation;
+            let up = frame.to_local(*scattered.direction().unit());
+            if up.z() <= 0.0 {
+                // The base transmitted the light below the surface.
+                return Some((scattered, attenuation));
+            }
+            attenuation = attenuation * self.coat_transmittance(up.z());
+
+            // Seen from inside of the coat, the surface normal points down.
+            let wo = Vec3::new(-up.x(), -up.y(), up.z());
+            let (wi, weight) = self
+                .distribution
+                .sample_dielectric(wo, 1.0 / self.refraction_index)?;
+            attenuation = attenuation * weight;
+            w = Vec3::new(wi.x(), wi.y(), -wi.z());
+            if w.z() > 0.0 {
+                let scattered = ray.spawn(hit_record.p(), frame.to_world(w));
+                return Some((scattered, attenuation));
+            }
+        }
+        None
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        if wo.z() <= 0.0 || !hit_record.front_face() {
+            return self.base.eval(ray, hit_record, direction);
+        }
+        if wi.z() <= 0.0 {
+            return Color::black();
+        }
+
+        // The highlight on top of the coat.
+        let highlight = match self.distribution.eval_reflection(wo, wi) {
+            Some((wm, value)) => fresnel_dielectric(wo.dot(wm), self.refraction_index) * value,
+            None => 0.0,
+        };
+
+        // The light that passes through the coat to the base and back. This
+        // ignores that the coat bends the light and reflects it internally,
+        // unlike [Coated::scatter]. Thus, the coat keeps the default density
+        // of 0, such that this estimate is only used for lights that
+        // scattered rays cannot find.
+        let refracted_cos = |cos_theta: f32| {
+            let sin2_theta = (1.0 - cos_theta * cos_theta) / self.refraction_index.powi(2);
+            (1.0 - sin2_theta).max(0.0).sqrt()
+        };
+        let transmitted = (1.0 - fresnel_dielectric(wo.z(), self.refraction_index))
+            * (1.0 - fresnel_dielectric(wi.z(), self.refraction_index));
+        let base = self.base.eval(ray, hit_record, direction)
+            * self.coat_transmittance(refracted_cos(wo.z()))
+            * self.coat_transmittance(refracted_cos(wi.z()))
+            * transmitted;
+        Color::white() * highlight + base
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        self.base.emitted(ray, hit_record)
+    }
+
+    fn opacity(&self, u: f32, v: f32, p: Point) -> f32 {
+        self.base.opacity(u, v, p)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.base.is_dispersive()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A blend of two materials. Each scatter picks one of the materials at
+/// random, where the probability of the second material is given by a weight
+/// texture. E.g., rust spots on metal or moss on stone.
+pub struct MixMaterial {
+    first: Arc<dyn Material>,
+    second: Arc<dyn Material>,
+    /// The probability of the second material is the luminance of this
+    /// texture.
+    weight: Arc<dyn Texture>,
+}
+
+impl MixMaterial {
+    /// Create a new blend of `first` and `second` material. The luminance of
+    /// the `weight` texture in `[0, 1]` is the fraction of the second
+    /// material.
+    pub fn new(
+        first: Arc<dyn Material>,
+        second: Arc<dyn Material>,
+        weight: Arc<dyn Texture>,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            weight,
+        }
+    }
+
+    /// Create a new blend of `first` and `second` material with a constant
+    /// `weight` of the second material in `[0, 1]`.
+    pub fn from_weight(first: Arc<dyn Material>, second: Arc<dyn Material>, weight: f32) -> Self {
+        Self::new(
+            first,
+            second,
+            Arc::new(SolidColor::from_rbg(weight, weight, weight)),
+        )
+    }
+
+    /// The weight of the second material at the texture coordinates.
+    fn weight(&self, u: f32, v: f32, p: Point) -> f32 {
+        self.weight.value(u, v, p).luminance().clamp(0.0, 1.0)
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        let weight = self.weight(hit_record.u(), hit_record.v(), hit_record.p());
+        if random_0_1_f32() < weight {
+            self.second.scatter(ray, hit_record)
+        } else {
+            self.first.scatter(ray, hit_record)
+        }
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let weight = self.weight(hit_record.u(), hit_record.v(), hit_record.p());
+        self.first.eval(ray, hit_record, direction) * (1.0 - weight)
+            + self.second.eval(ray, hit_record, direction) * weight
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        // The density is only known if both materials know theirs. Otherwise,
+        // a ray that the unknown one scattered would be weighed by the
+        // density of the other one.
+        let weight = self.weight(hit_record.u(), hit_record.v(), hit_record.p());
+        let first = self.first.pdf(ray, hit_record, direction);
+        let second = self.second.pdf(ray, hit_record, direction);
+        if (first <= 0.0 && weight < 1.0) || (second <= 0.0 && weight > 0.0) {
+            return 0.0;
+        }
+        first * (1.0 - weight) + second * weight
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        let weight = self.weight(hit_record.u(), hit_record.v(), hit_record.p());
+        self.first.emitted(ray, hit_record) * (1.0 - weight)
+            + self.second.emitted(ray, hit_record) * weight
+    }
+
+    fn opacity(&self, u: f32, v: f32, p: Point) -> f32 {
+        let weight = self.weight(u, v, p);
+        self.first.opacity(u, v, p) * (1.0 - weight) + self.second.opacity(u, v, p) * weight
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.first.is_dispersive() || self.second.is_dispersive()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A material whose opacity is given by a mask texture, e.g., to cut leaves
+/// or a fence out of a single quad. Rays pass through the transparent parts
+/// of the mask, and the opaque parts scatter like the wrapped material.
+pub struct AlphaMask {
+    material: Arc<dyn Material>,
+    /// The opacity is the luminance of this texture.
+    mask: Arc<dyn Texture>,
+    /// If set, the surface is fully opaque where the mask reaches this
+    /// threshold and fully transparent elsewhere.
+    threshold: Option<f32>,
+}
+
+impl AlphaMask {
+    /// Cut out the parts of `material` where the luminance of `mask` is
+    /// below 1. Parts with a luminance between 0 and 1 are partially
+    /// transparent. E.g., use
+    /// [ImageTexture::from_alpha](crate::texture::ImageTexture::from_alpha)
+    /// to use the alpha channel of an image as the mask.
+    pub fn new(material: Arc<dyn Material>, mask: Arc<dyn Texture>) -> Self {
+        Self {
+            material,
+            mask,
+            threshold: None,
+        }
+    }
+
+    /// Cut out the parts of `material` where the luminance of `mask` is
+    /// below `threshold`, and keep the rest fully opaque.
+    pub fn with_threshold(
+        material: Arc<dyn Material>,
+        mask: Arc<dyn Texture>,
+        threshold: f32,
+    ) -> Self {
+        Self {
+            material,
+            mask,
+            threshold: Some(threshold),
+        }
+    }
+}
+
+impl Material for AlphaMask {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        self.material.scatter(ray, hit_record)
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        self.material.eval(ray, hit_record, direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        self.material.pdf(ray, hit_record, direction)
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        self.material.emitted(ray, hit_record)
+    }
+
+    fn opacity(&self, u: f32, v: f32, p: Point) -> f32 {
+        let luminance = self.mask.value(u, v, p).luminance();
+        let opacity = match self.threshold {
+            Some(threshold) if luminance >= threshold => 1.0,
+            Some(_) => 0.0,
+            None => luminance,
+        };
+        opacity * self.material.opacity(u, v, p)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.material.is_dispersive()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A material whose shading normal is perturbed by a tangent space normal
+/// map before the wrapped material scatters. The red, green, and blue
+/// channels of the map are the components of the normal along the tangent,
+/// the bitangent, and the surface normal, mapped from `[-1, 1]` to `[0, 1]`.
+pub struct NormalMap {
+    material: Arc<dyn Material>,
+    normal_map: Arc<dyn Texture>,
+    /// How strongly the normal is perturbed, where 1 is the normal map as is.
+    strength: f32,
+}
+
+impl NormalMap {
+    /// Perturb the normal of `material` by the `normal_map` texture.
+    pub fn new(material: Arc<dyn Material>, normal_map: Arc<dyn Texture>, strength: f32) -> Self {
+        Self {
+            material,
+            normal_map,
+            strength,
+        }
+    }
+
+    /// Perturb the normal of `material` by the normal map image at the given
+    /// path. Panics if the loading fails.
+    pub fn from_image(material: Arc<dyn Material>, path: impl AsRef<Path>) -> Self {
+        Self::new(material, Arc::new(ImageTexture::new(path)), 1.0)
+    }
+}
+
+impl NormalMap {
+    /// Replace the shading normal of the hit by the normal map.
+    fn perturb(&self, ray: &Ray, hit_record: &mut HitRecord) {
+        let color = self
+            .normal_map
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        let local = Vec3::new(
+            self.strength * (2.0 * color.r() - 1.0),
+            self.strength * (2.0 * color.g() - 1.0),
+            2.0 * color.b() - 1.0,
+        );
+        // The map is in the frame of the outside of the surface, such that
+        // it looks the same from both sides.
+        let frame = ONB::from_tangent(hit_record.outward_normal(), *hit_record.tangent());
+        let mut normal = frame.to_world(local);
+        if !hit_record.front_face() {
+            normal = -normal;
+        }
+        perturb_normal(ray, hit_record, normal);
+    }
+}
+
+impl Material for NormalMap {
+    fn scatter(&self, ray: &Ray, mut hit_record: HitRecord) -> Option<(Ray, Color)> {
+        self.perturb(ray, &mut hit_record);
+        self.material.scatter(ray, hit_record)
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let mut hit_record = hit_record.copy();
+        self.perturb(ray, &mut hit_record);
+        self.material.eval(ray, &hit_record, direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let mut hit_record = hit_record.copy();
+        self.perturb(ray, &mut hit_record);
+        self.material.pdf(ray, &hit_record, direction)
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        self.material.emitted(ray, hit_record)
+    }
+
+    fn opacity(&self, u: f32, v: f32, p: Point) -> f32 {
+        self.material.opacity(u, v, p)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.material.is_dispersive()
+    }
+}
+
+/// The offset in texture coordinates and space used to estimate the slope
+/// of a bump map.
+const BUMP_DELTA: f32 = 0.0005;
+
+#[derive(Debug, Clone)]
+/// A material whose shading normal is perturbed by a height map before the
+/// wrapped material scatters. The surface looks as if it were displaced
+/// along its normal by the height, which is the luminance of a texture.
+pub struct BumpMap {
+    material: Arc<dyn Material>,
+    height: Arc<dyn Texture>,
+    /// The height of the bumps.
+    scale: f32,
+}
+
+impl BumpMap {
+    /// Perturb the normal of `material` by the luminance of the `height`
+    /// texture, which is multiplied by `scale`.
+    pub fn new(material: Arc<dyn Material>, height: Arc<dyn Texture>, scale: f32) -> Self {
+        Self {
+            material,
+            height,
+            scale,
+        }
+    }
+}
+
+impl BumpMap {
+    /// Replace the shading normal of the hit by the slope of the height.
+    fn perturb(&self, ray: &Ray, hit_record: &mut HitRecord) {
+        // The surface is displaced along its outward normal. Surfaces that do
+        // not provide the derivatives of the point are assumed to map the
+        // texture coordinates to the tangent and bitangent at unit scale.
+        let normal = hit_record.outward_normal();
+        let (dpdu, dpdv) = hit_record.partials().unwrap_or_else(|| {
+            let frame = ONB::from_tangent(normal, *hit_record.tangent());
+            (*frame.u(), *frame.v())
+        });
+
+        // Estimate the derivatives of the height by forward differences.
+        // Textures may depend on the texture coordinates or the point, so
+        // both are offset.
+        let (u, v, p) = (hit_record.u(), hit_record.v(), hit_record.p());
+        let height = |u: f32, v: f32, p: Point| self.scale * self.height.value(u, v, p).luminance();
+        let h = height(u, v, p);
+        let dhdu = (height(u + BUMP_DELTA, v, p + BUMP_DELTA * dpdu) - h) / BUMP_DELTA;
+        let dhdv = (height(u, v + BUMP_DELTA, p + BUMP_DELTA * dpdv) - h) / BUMP_DELTA;
+
+        // The normal of the displaced surface, oriented like the outward
+        // normal and then turned to the side of the surface that was hit.
+        let mut bumped = (dpdu + dhdu * *normal).cross(dpdv + dhdv * *normal);
+        if dpdu.cross(dpdv).dot(*normal) < 0.0 {
+            bumped = -bumped;
+        }
+        if !hit_record.front_face() {
+            bumped = -bumped;
+        }
+        perturb_normal(ray, hit_record, bumped);
+    }
+}
+
+impl Material for BumpMap {
+    fn scatter(&self, ray: &Ray, mut hit_record: HitRecord) -> Option<(Ray, Color)> {
+        self.perturb(ray, &mut hit_record);
+        self.material.scatter(ray, hit_record)
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let mut hit_record = hit_record.copy();
+        self.perturb(ray, &mut hit_record);
+        self.material.eval(ray, &hit_record, direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let mut hit_record = hit_record.copy();
+        self.perturb(ray, &mut hit_record);
+        self.material.pdf(ray, &hit_record, direction)
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        self.material.emitted(ray, hit_record)
+    }
+
+    fn opacity(&self, u: f32, v: f32, p: Point) -> f32 {
+        self.material.opacity(u, v, p)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.material.is_dispersive()
+    }
+}
+
+/// Replace the shading normal of the hit by `normal`. A perturbed normal that
+/// faces away from the ray would scatter light into the surface, so such
+/// normals are ignored.
+fn perturb_normal(ray: &Ray, hit_record: &mut HitRecord, normal: Vec3) {
+    if normal.near_zero() || normal.dot(*ray.direction()) >= 0.0 {
+        return;
+    }
+    let tangent = hit_record.tangent();
+    hit_record.set_shading_normal(normal.unit());
+    hit_record.set_tangent(*tangent);
+}
+
+#[derive(Debug, Clone)]
+/// A struct that implements a source of diffuse light. By default, the light
+/// is emitted equally from both faces of the surface and into all directions.
 pub struct DiffuseLight {
     texture: Arc<dyn Texture>,
+    two_sided: bool,
+    /// The exponent of the cosine between the normal and the direction of
+    /// the emitted light.
+    cosine_power: f32,
+    /// The cosines of the angles to the normal where the light starts to fall
+    /// off, and where it is cut off.
+    spot: Option<(f32, f32)>,
+    profile: Option<Arc<IesProfile>>,
 }
 
 impl DiffuseLight {
     /// Create a light source.
     pub fn new(texture: Arc<dyn Texture>) -> Self {
-        Self { texture }
+        Self {
+            texture,
+            two_sided: true,
+            cosine_power: 0.0,
+            spot: None,
+            profile: None,
+        }
+    }
+
+    /// Create a light source of a single color.
+    pub fn from_color(color: Color) -> Self {
+        Self::new(Arc::new(SolidColor::new(color)))
+    }
+
+    /// Only emit light from the front face of the surface, e.g., for a panel
+    /// that lights a room but is dark from behind.
+    pub fn one_sided(mut self) -> Self {
+        self.two_sided = false;
+        self
+    }
+
+    /// Scale the emitted light by the cosine between the normal and the
+    /// direction of the light raised to the power of `exponent`. A higher
+    /// exponent focuses the light along the normal.
+    pub fn with_cosine_power(mut self, exponent: f32) -> Self {
+        self.cosine_power = exponent;
+        self
+    }
+
+    /// Restrict the light to a cone around the normal, like a spotlight. The
+    /// light smoothly falls off from the angle `falloff_start` to the angle
+    /// `cone_angle`, both in degrees, and is cut off outside of the cone.
+    pub fn with_spot(mut self, cone_angle: f32, falloff_start: f32) -> Self {
+        let cos_cone = degrees_to_radians(cone_angle).cos();
+        let cos_falloff = degrees_to_radians(falloff_start.min(cone_angle)).cos();
+        self.spot = Some((cos_falloff, cos_cone));
+        self
+    }
+
+    /// Shape the emitted light by a photometric profile, whose nadir points
+    /// along the normal and whose C0 plane contains the tangent of the
+    /// surface, e.g., the first edge of a [Quad](crate::quad::Quad). The
+    /// color of the light is reached where the profile is brightest.
+    pub fn with_profile(mut self, profile: Arc<IesProfile>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// The fraction of the light that is emitted at the angle `cos_theta` to
+    /// the normal.
+    fn falloff(&self, cos_theta: f32) -> f32 {
+        let mut falloff = if self.cosine_power > 0.0 {
+            cos_theta.max(0.0).powf(self.cosine_power)
+        } else {
+            1.0
+        };
+        if let Some((cos_falloff, cos_cone)) = self.spot {
+            if cos_theta < cos_cone {
+                return 0.0;
+            }
+            if cos_theta < cos_falloff {
+                let t = (cos_theta - cos_cone) / (cos_falloff - cos_cone);
+                falloff *= t * t * (3.0 - 2.0 * t);
+            }
+        }
+        falloff
     }
 }
 
@@ -172,8 +1418,22 @@ impl Material for DiffuseLight {
         None
     }
 
-    fn emitted(&self, u: f32, v: f32, p: Point) -> Color {
-        self.texture.value(u, v, p)
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        if !self.two_sided && !hit_record.front_face() {
+            return Color::black();
+        }
+        // The normal of the hit faces the origin of the ray.
+        let direction = -*ray.direction().unit();
+        let cos_theta = direction.dot(*hit_record.normal());
+        let mut falloff = self.falloff(cos_theta);
+        if let Some(profile) = &self.profile {
+            let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+            falloff *= profile.relative_intensity(frame.to_local(direction));
+        }
+        let color = self
+            .texture
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        color * falloff
     }
 }
 
@@ -199,10 +1459,343 @@ impl Isotropic {
 
 impl Material for Isotropic {
     fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
-        let scattered = Ray::new(hit_record.p(), *random_unit_vector(), ray.time());
+        let scattered = ray.spawn(hit_record.p(), *random_unit_vector());
         let attenuation = self
             .texture
             .value(hit_record.u(), hit_record.v(), hit_record.p());
         Some((scattered, attenuation))
     }
+
+    fn eval(&self, _ray: &Ray, hit_record: &HitRecord, _direction: Vec3) -> Color {
+        let albedo = self
+            .texture
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        albedo * (1.0 / (4.0 * PI))
+    }
+
+    fn pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vec3) -> f32 {
+        1.0 / (4.0 * PI)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A material for hair and fur after the model by Kajiya and Kay. Light is
+/// scattered around the tangent of the hair, i.e., the direction of the fiber,
+/// instead of around the normal. It has a diffuse lobe which scatters light
+/// proportional to the sine of the angle with the fiber, and a specular lobe
+/// around the cone of directions that mirror the incoming ray along the fiber.
+pub struct Hair {
+    texture: Arc<dyn Texture>,
+    specular: Color,
+    exponent: f32,
+}
+
+impl Hair {
+    /// Create a new hair material.
+    ///
+    /// * `albedo` - The color of the diffuse lobe.
+    /// * `specular` - The color of the specular lobe.
+    /// * `exponent` - The exponent of the specular lobe. A bigger exponent
+    ///   means a sharper highlight.
+    pub fn new(albedo: Color, specular: Color, exponent: f32) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)), specular, exponent)
+    }
+
+    /// Create a new hair material whose diffuse color is given by a texture.
+    pub fn from_texture(texture: Arc<dyn Texture>, specular: Color, exponent: f32) -> Self {
+        Self {
+            texture,
+            specular,
+            exponent,
+        }
+    }
+
+    /// The probability that a scattered ray is sampled from the specular
+    /// lobe instead of the diffuse lobe with the given `albedo`, or [None] if
+    /// the hair is black.
+    fn specular_probability(&self, albedo: Color) -> Option<f32> {
+        let specular_weight = self.specular.luminance();
+        let total_weight = specular_weight + albedo.luminance();
+        if total_weight <= 0.0 {
+            return None;
+        }
+        Some(specular_weight / total_weight)
+    }
+
+    /// The density with respect to the solid angle that the specular lobe
+    /// scatters a ray at the angle `theta_o` to the fiber into the angle
+    /// `theta_i`. The angle deviates from the cone of mirror directions by an
+    /// angle whose cosine is distributed like `u^(1 / (exponent + 1))` and is
+    /// folded back at the ends of the fiber, such that it stays in `[0, PI]`.
+    fn specular_density(&self, theta_o: f32, theta_i: f32) -> f32 {
+        let sin_theta_i = theta_i.sin();
+        if sin_theta_i < 1e-4 {
+            return 0.0;
+        }
+        let deviation_density = |deviation: f32| {
+            let deviation = deviation.abs();
+            if deviation < PI / 2.0 {
+                0.5 * (self.exponent + 1.0) * deviation.cos().powf(self.exponent) * deviation.sin()
+            } else {
+                0.0
+            }
+        };
+        // The deviations that lead to `theta_i` directly and folded back.
+        let density = deviation_density(theta_i - theta_o)
+            + deviation_density(-theta_i - theta_o)
+            + deviation_density(2.0 * PI - theta_i - theta_o);
+        density / (2.0 * PI * sin_theta_i)
+    }
+}
+
+impl Material for Hair {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        let albedo = self
+            .texture
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        let specular_probability = self.specular_probability(albedo)?;
+        let frame = ONB::new(hit_record.tangent());
+
+        let (direction, attenuation) = if random_0_1_f32() < specular_probability {
+            // Keep the component of the direction along the fiber, which
+            // yields the cone of mirror directions, and perturb its angle.
+            let theta = ray
+                .direction()
+                .unit()
+                .dot(*frame.w())
+                .clamp(-1.0, 1.0)
+                .acos();
+            let deviation = random_0_1_f32().powf(1.0 / (self.exponent + 1.0)).acos();
+            let deviation = if random_0_1_f32() < 0.5 {
+                deviation
+            } else {
+                -deviation
+            };
+            let theta = match theta + deviation {
+                theta if theta < 0.0 => -theta,
+                theta if theta > PI => 2.0 * PI - theta,
+                theta => theta,
+            };
+            let phi = 2.0 * PI * random_0_1_f32();
+            let local = Vec3::new(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            );
+            (
+                frame.to_world(local),
+                self.specular * (1.0 / specular_probability),
+            )
+        } else {
+            // Sample directions proportional to the sine of their angle with
+            // the fiber by rejection sampling.
+            let direction = loop {
+                let direction = random_unit_vector();
+                let cos_theta = direction.dot(*frame.w());
+                let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+                if random_0_1_f32() < sin_theta {
+                    break *direction;
+                }
+            };
+            (direction, albedo * (1.0 / (1.0 - specular_probability)))
+        };
+
+        let scattered = ray.spawn(hit_record.p(), direction);
+        Some((scattered, attenuation))
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        // Both lobes scatter light with the density with which they are
+        // sampled, weighted by their color.
+        let albedo = self
+            .texture
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        let fiber = hit_record.tangent();
+        let theta_o = ray.direction().unit().dot(*fiber).clamp(-1.0, 1.0).acos();
+        let theta_i = direction.unit().dot(*fiber).clamp(-1.0, 1.0).acos();
+        let sin_theta_i = theta_i.sin();
+        if sin_theta_i < 1e-4 {
+            return Color::black();
+        }
+
+        // The diffuse lobe is proportional to the sine, which integrates to
+        // PI^2 over the sphere.
+        let diffuse = albedo * (sin_theta_i / (PI * PI));
+        diffuse + self.specular * self.specular_density(theta_o, theta_i)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let albedo = self
+            .texture
+            .value(hit_record.u(), hit_record.v(), hit_record.p());
+        let Some(specular_probability) = self.specular_probability(albedo) else {
+            return 0.0;
+        };
+        let fiber = hit_record.tangent();
+        let theta_o = ray.direction().unit().dot(*fiber).clamp(-1.0, 1.0).acos();
+        let theta_i = direction.unit().dot(*fiber).clamp(-1.0, 1.0).acos();
+        let diffuse = theta_i.sin() / (PI * PI);
+        (1.0 - specular_probability) * diffuse
+            + specular_probability * self.specular_density(theta_o, theta_i)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        color::Color,
+        hittable::{HitRecord, Hittable},
+        interval::Interval,
+        microfacet::TrowbridgeReitz,
+        point::Point,
+        quad::Quad,
+        random_cosine_direction, random_unit_vector,
+        ray::Ray,
+        texture::SolidColor,
+        vec3::Vec3,
+    };
+
+    use super::{
+        BumpMap, Coated, Dielectric, DiffuseLight, Lambertian, Material, Metal, MixMaterial,
+        OrenNayar, RoughDielectric,
+    };
+
+    /// A hit at the origin of a surface that faces up, by a ray that arrives
+    /// from `direction`.
+    fn hit_from(direction: Vec3, material: Arc<dyn Material>) -> (Ray, HitRecord) {
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0) - direction, direction, 0.0);
+        let hit_record = HitRecord::new(
+            &ray,
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0).unit(),
+            1.0,
+            0.5,
+            0.5,
+            material,
+        );
+        (ray, hit_record)
+    }
+
+    #[test]
+    fn tint_is_reached_after_its_distance() {
+        let color = Color::new(0.2, 0.5, 0.8);
+        let glass = Arc::new(Dielectric::tinted(1.5, color, 2.0));
+        // The ray leaves the glass through the back face after 2 units.
+        let (ray, hit_record) = hit_from(Vec3::new(0.0, 2.0, 0.0), glass.clone());
+        let (_, attenuation) = glass.scatter(&ray, hit_record).unwrap();
+        assert!((attenuation.r() - color.r()).abs() < 1e-4);
+        assert!((attenuation.g() - color.g()).abs() < 1e-4);
+        assert!((attenuation.b() - color.b()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn smooth_oren_nayar_is_lambertian() {
+        let albedo = Color::new(0.8, 0.4, 0.2);
+        let material = Arc::new(OrenNayar::new(albedo, 0.0));
+        for _ in 0..100 {
+            let wo = random_cosine_direction();
+            let wi = random_cosine_direction();
+            assert!((material.reflectance(wo, wi) - 1.0).abs() < 1e-5);
+        }
+        let (ray, hit_record) = hit_from(Vec3::new(1.0, -1.0, 0.0), material.clone());
+        let (_, attenuation) = material.scatter(&ray, hit_record).unwrap();
+        assert!((attenuation.r() - albedo.r()).abs() < 1e-5);
+        assert!((attenuation.b() - albedo.b()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn mix_weight_selects_a_material() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+        for (weight, color) in [(0.0, red), (1.0, blue)] {
+            let mix = Arc::new(MixMaterial::from_weight(
+                Arc::new(Lambertian::new(red)),
+                Arc::new(Lambertian::new(blue)),
+                weight,
+            ));
+            for _ in 0..100 {
+                let (ray, hit_record) = hit_from(Vec3::new(1.0, -1.0, 0.0), mix.clone());
+                let (_, attenuation) = mix.scatter(&ray, hit_record).unwrap();
+                assert_eq!((attenuation.r(), attenuation.b()), (color.r(), color.b()));
+            }
+        }
+    }
+
+    #[test]
+    fn constant_height_keeps_the_normal() {
+        let bump = Arc::new(BumpMap::new(
+            Arc::new(Lambertian::new(Color::white())),
+            Arc::new(SolidColor::new(Color::new(0.5, 0.5, 0.5))),
+            2.0,
+        ));
+        let quad = Quad::new(
+            Point::new(-1.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -2.0),
+            bump.clone(),
+        );
+        let ray = Ray::new(Point::new(0.2, 1.0, 0.3), Vec3::new(0.1, -1.0, 0.2), 0.0);
+        let mut hit_record = quad.hit(&ray, Interval::universe()).unwrap();
+        let normal = hit_record.normal();
+        bump.perturb(&ray, &mut hit_record);
+        assert!((hit_record.normal().dot(*normal) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn anisotropic_metal_with_equal_roughness_is_isotropic() {
+        let metal = Metal::anisotropic(Color::white(), 0.4, 0.4);
+        let anisotropic = metal.distribution.unwrap();
+        let isotropic = TrowbridgeReitz::from_roughness(0.4);
+        for _ in 0..100 {
+            let wm = random_cosine_direction();
+            assert!((anisotropic.d(wm) - isotropic.d(wm)).abs() < 1e-4 * isotropic.d(wm));
+            assert!((anisotropic.g1(wm) - isotropic.g1(wm)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn one_sided_light_is_dark_from_behind() {
+        let light = Arc::new(DiffuseLight::from_color(Color::white()).one_sided());
+        let (ray, front) = hit_from(Vec3::new(0.0, -1.0, 0.0), light.clone());
+        assert!(light.emitted(&ray, &front).luminance() > 0.0);
+        let (ray, back) = hit_from(Vec3::new(0.0, 1.0, 0.0), light.clone());
+        assert_eq!(light.emitted(&ray, &back).luminance(), 0.0);
+    }
+
+    #[test]
+    fn rough_glass_eval_and_pdf_agree() {
+        let glass = Arc::new(RoughDielectric::new(1.5, 0.5));
+        for arriving in [Vec3::new(1.0, -2.0, 0.0), Vec3::new(1.0, 2.0, 0.0)] {
+            let (ray, hit_record) = hit_from(arriving, glass.clone());
+            for _ in 0..1000 {
+                let direction = *random_unit_vector();
+                let value = glass.eval(&ray, &hit_record, direction);
+                let pdf = glass.pdf(&ray, &hit_record, direction);
+                assert_eq!(value.luminance() > 0.0, pdf > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn coat_leaves_lights_to_scattered_rays() {
+        let coated: Arc<dyn Material> = Arc::new(Coated::new(
+            Arc::new(Lambertian::new(Color::white())),
+            1.5,
+            0.3,
+        ));
+        let mix = Arc::new(MixMaterial::from_weight(
+            Arc::new(Lambertian::new(Color::white())),
+            coated.clone(),
+            0.5,
+        ));
+        let direction = *Vec3::new(1.0, 1.0, 0.0).unit();
+        for material in [coated, mix] {
+            // Lights that scattered rays cannot find still light the coat.
+            let (ray, hit_record) = hit_from(Vec3::new(1.0, -1.0, 0.0), material.clone());
+            assert!(material.eval(&ray, &hit_record, direction).luminance() > 0.0);
+            assert_eq!(material.pdf(&ray, &hit_record, direction), 0.0);
+        }
+    }
 }
@@ -0,0 +1,499 @@
+//! This module contains the building blocks of microfacet materials: the
+//! Trowbridge-Reitz (GGX) distribution of microfacet normals and the Fresnel
+//! equations. All directions are given in a local shading frame where the
+//! surface normal is the z-axis, see [ONB](crate::onb::ONB).
+
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::{random_0_1_f32, vec3::Vec3, PI};
+
+/// Below this `alpha` a surface is treated as a perfect mirror.
+const SMOOTH_ALPHA: f32 = 1e-3;
+
+#[derive(Copy, Clone, Debug)]
+/// The Trowbridge-Reitz (GGX) distribution of microfacet normals. The
+/// roughness along the x and y axes of the shading frame can differ, which
+/// yields anisotropic reflection.
+pub struct TrowbridgeReitz {
+    alpha_x: f32,
+    alpha_y: f32,
+}
+
+impl TrowbridgeReitz {
+    /// Create a new distribution from its `alpha` along the x and y axes.
+    pub fn new(alpha_x: f32, alpha_y: f32) -> Self {
+        Self {
+            alpha_x: alpha_x.max(1e-4),
+            alpha_y: alpha_y.max(1e-4),
+        }
+    }
+
+    /// Create a new isotropic distribution from a perceptual `roughness` in
+    /// `[0, 1]`. The `alpha` of the distribution is the square of the roughness.
+    pub fn from_roughness(roughness: f32) -> Self {
+        let alpha = roughness * roughness;
+        Self::new(alpha, alpha)
+    }
+
+    /// Create a new anisotropic distribution from the perceptual roughness
+    /// along the x and y axes.
+    pub fn from_anisotropic_roughness(roughness_x: f32, roughness_y: f32) -> Self {
+        Self::new(roughness_x * roughness_x, roughness_y * roughness_y)
+    }
+
+    #[inline]
+    /// Returns true iff the distribution is so narrow that it can be treated
+    /// as a perfectly smooth surface.
+    pub fn effectively_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) < SMOOTH_ALPHA
+    }
+
+    /// The density of microfacets with normal `wm`.
+    pub fn d(&self, wm: Vec3) -> f32 {
+        let cos2_theta = wm.z() * wm.z();
+        let sin2_theta = (1.0 - cos2_theta).max(0.0);
+        let cos4_theta = cos2_theta * cos2_theta;
+        if cos4_theta < 1e-16 {
+            return 0.0;
+        }
+        let tan2_theta = sin2_theta / cos2_theta;
+        let (cos_phi, sin_phi) = cos_sin_phi(wm);
+        let e = tan2_theta * ((cos_phi / self.alpha_x).powi(2) + (sin_phi / self.alpha_y).powi(2));
+        1.0 / (PI * self.alpha_x * self.alpha_y * cos4_theta * (1.0 + e) * (1.0 + e))
+    }
+
+    /// The auxiliary function of the masking-shadowing function.
+    fn lambda(&self, w: Vec3) -> f32 {
+        let cos2_theta = w.z() * w.z();
+        if cos2_theta == 0.0 {
+            return 0.0;
+        }
+        let tan2_theta = (1.0 - cos2_theta).max(0.0) / cos2_theta;
+        let (cos_phi, sin_phi) = cos_sin_phi(w);
+        let alpha2 = (cos_phi * self.alpha_x).powi(2) + (sin_phi * self.alpha_y).powi(2);
+        ((1.0 + alpha2 * tan2_theta).sqrt() - 1.0) / 2.0
+    }
+
+    /// The fraction of microfacets that are visible from direction `w`.
+    pub fn g1(&self, w: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// The fraction of microfacets that are visible from both `wo` and `wi`.
+    pub fn g(&self, wo: Vec3, wi: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// The density of microfacet normals `wm` that are visible from `w`.
+    /// This is the density with which [TrowbridgeReitz::sample_wm] samples `wm`.
+    pub fn pdf(&self, w: Vec3, wm: Vec3) -> f32 {
+        let cos_theta = w.z().abs();
+        if cos_theta == 0.0 {
+            return 0.0;
+        }
+        self.g1(w) / cos_theta * self.d(wm) * w.dot(wm).abs()
+    }
+
+    /// Evaluate the reflection from `wo` into `wi` without the Fresnel term
+    /// and times the cosine of `wi`, i.e., `D * G / (4 * cos(theta_o))`.
+    /// Also returns the microfacet normal between both directions. Returns
+    /// [None] if a direction is below the surface or if the distribution is
+    /// effectively smooth, such that no other direction reflects into `wo`.
+    pub fn eval_reflection(&self, wo: Vec3, wi: Vec3) -> Option<(Vec3, f32)> {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 || self.effectively_smooth() {
+            return None;
+        }
+        let wm = wo + wi;
+        if wm.near_zero() {
+            return None;
+        }
+        let wm = *wm.unit();
+        Some((wm, self.d(wm) * self.g(wo, wi) / (4.0 * wo.z())))
+    }
+
+    /// The density with which reflecting `wo` about a microfacet normal
+    /// sampled by [TrowbridgeReitz::sample_wm] yields `wi`, i.e.,
+    /// `pdf(wm) / (4 * |wo · wm|)`. It is 0 if the distribution is
+    /// effectively smooth, like [TrowbridgeReitz::eval_reflection].
+    pub fn pdf_reflection(&self, wo: Vec3, wi: Vec3) -> f32 {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 || self.effectively_smooth() {
+            return 0.0;
+        }
+        let wm = wo + wi;
+        if wm.near_zero() {
+            return 0.0;
+        }
+        let wm = *wm.unit();
+        self.pdf(wo, wm) / (4.0 * wo.dot(wm).abs())
+    }
+
+    /// The microfacet normal through which light that arrives from `wo` is
+    /// reflected or refracted into `wi`, i.e., the generalized half vector.
+    /// Returns [None] if the distribution is effectively smooth or if no
+    /// microfacet faces both directions.
+    fn dielectric_wm(&self, wo: Vec3, wi: Vec3, eta: f32) -> Option<Vec3> {
+        if wo.z() <= 0.0 || wi.z() == 0.0 || self.effectively_smooth() {
+            return None;
+        }
+        let wm = if wi.z() > 0.0 { wo + wi } else { wo + eta * wi };
+        if wm.near_zero() {
+            return None;
+        }
+        let wm = *wm.unit();
+        let wm = if wm.z() < 0.0 { -wm } else { wm };
+        // Microfacets that face away from either direction do not scatter.
+        if wm.dot(wo) <= 0.0 || wm.dot(wi) * wi.z() <= 0.0 {
+            return None;
+        }
+        Some(wm)
+    }
+
+    /// Evaluate how light that arrives from `wi` is reflected or refracted
+    /// into `wo` at a rough interface between two dielectrics, times the
+    /// cosine of `wi`, where `eta` is the ratio of the refractive index below
+    /// the surface over the one above it. Like
+    /// [TrowbridgeReitz::sample_dielectric], it leaves out the scaling of
+    /// radiance by `1 / eta^2` at refraction, which cancels out once light
+    /// leaves the medium again. It is 0 if the distribution is effectively
+    /// smooth.
+    pub fn eval_dielectric(&self, wo: Vec3, wi: Vec3, eta: f32) -> f32 {
+        let Some(wm) = self.dielectric_wm(wo, wi, eta) else {
+            return 0.0;
+        };
+        let reflectance = fresnel_dielectric(wo.dot(wm), eta);
+        let value = self.d(wm) * self.g(wo, wi) / wo.z();
+        if wi.z() > 0.0 {
+            reflectance * value / 4.0
+        } else {
+            // The Jacobian of the refraction relates the microfacet normal to
+            // the refracted direction.
+            let denominator = (wi.dot(wm) + wo.dot(wm) / eta).powi(2);
+            (1.0 - reflectance) * value * (wo.dot(wm) * wi.dot(wm)).abs() / denominator
+        }
+    }
+
+    /// The density with which [TrowbridgeReitz::sample_dielectric] yields
+    /// `wi` for light that arrives from `wo`, including the probability of
+    /// choosing between reflection and refraction. It is 0 if the
+    /// distribution is effectively smooth.
+    pub fn pdf_dielectric(&self, wo: Vec3, wi: Vec3, eta: f32) -> f32 {
+        let Some(wm) = self.dielectric_wm(wo, wi, eta) else {
+            return 0.0;
+        };
+        let reflectance = fresnel_dielectric(wo.dot(wm), eta);
+        if wi.z() > 0.0 {
+            reflectance * self.pdf(wo, wm) / (4.0 * wo.dot(wm))
+        } else {
+            let denominator = (wi.dot(wm) + wo.dot(wm) / eta).powi(2);
+            (1.0 - reflectance) * self.pdf(wo, wm) * wi.dot(wm).abs() / denominator
+        }
+    }
+
+    /// Sample a microfacet normal that is visible from `w`.
+    pub fn sample_wm(&self, w: Vec3) -> Vec3 {
+        // Transform `w` to the hemispherical configuration.
+        let wh = Vec3::new(self.alpha_x * w.x(), self.alpha_y * w.y(), w.z());
+        let wh = *wh.unit();
+        let wh = if wh.z() < 0.0 { -wh } else { wh };
+
+        // Find an orthonormal basis for the visible normal sampling space.
+        let t1 = if wh.z() < 0.99999 {
+            *Vec3::new(0.0, 0.0, 1.0).cross(wh).unit()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = wh.cross(t1);
+
+        // Sample a uniformly distributed point on the unit disk and warp it to
+        // the projection of the visible hemisphere.
+        let r = random_0_1_f32().sqrt();
+        let phi = 2.0 * PI * random_0_1_f32();
+        let (px, py) = (r * phi.cos(), r * phi.sin());
+        let h = (1.0 - px * px).sqrt();
+        let s = (1.0 + wh.z()) / 2.0;
+        let py = (1.0 - s) * h + s * py;
+
+        // Reproject to the hemisphere and transform the normal back to the
+        // ellipsoid configuration.
+        let pz = (1.0 - px * px - py * py).max(0.0).sqrt();
+        let nh = px * t1 + py * t2 + pz * wh;
+        *Vec3::new(
+            self.alpha_x * nh.x(),
+            self.alpha_y * nh.y(),
+            nh.z().max(1e-6),
+        )
+        .unit()
+    }
+
+    /// Sample how light that arrives from `wo` scatters at a rough interface
+    /// between two dielectrics, where `eta` is the ratio of the refractive
+    /// index below the surface over the one above it. A visible microfacet is
+    /// sampled, and the light is reflected or refracted through it according
+    /// to the exact Fresnel equations, which cancels them out. Returns the
+    /// direction, which points below the surface for refraction, and the
+    /// weight `f * cos(theta_i) / pdf = G / G1` of the sample.
+    pub fn sample_dielectric(&self, wo: Vec3, eta: f32) -> Option<(Vec3, f32)> {
+        let wm = if self.effectively_smooth() {
+            Vec3::new(0.0, 0.0, 1.0)
+        } else {
+            self.sample_wm(wo)
+        };
+        let reflectance = fresnel_dielectric(wo.dot(wm), eta);
+        let wi = if random_0_1_f32() < reflectance {
+            let wi = reflect(wo, wm);
+            if wi.z() <= 0.0 {
+                return None;
+            }
+            wi
+        } else {
+            let wi = refract(wo, wm, eta)?;
+            if wi.z() >= 0.0 {
+                return None;
+            }
+            wi
+        };
+        if self.effectively_smooth() {
+            return Some((wi, 1.0));
+        }
+        Some((wi, self.g(wo, wi) / self.g1(wo)))
+    }
+}
+
+/// The cosine and sine of the azimuthal angle of `w` in the shading frame.
+fn cos_sin_phi(w: Vec3) -> (f32, f32) {
+    let sin_theta = (1.0 - w.z() * w.z()).max(0.0).sqrt();
+    if sin_theta == 0.0 {
+        (1.0, 0.0)
+    } else {
+        (
+            (w.x() / sin_theta).clamp(-1.0, 1.0),
+            (w.y() / sin_theta).clamp(-1.0, 1.0),
+        )
+    }
+}
+
+/// Reflect `w` about the normal `n`, such that the result is on the same side
+/// of the surface as `w`.
+pub fn reflect(w: Vec3, n: Vec3) -> Vec3 {
+    -w + 2.0 * w.dot(n) * n
+}
+
+/// Refract `w` through the interface with normal `n`, where `eta` is the
+/// ratio of the refractive index on the other side of the interface over the
+/// refractive index on the side of `w`. Returns [None] on total internal
+/// reflection.
+pub fn refract(w: Vec3, n: Vec3, eta: f32) -> Option<Vec3> {
+    let (n, eta, cos_theta_i) = if w.dot(n) < 0.0 {
+        (-n, 1.0 / eta, -w.dot(n))
+    } else {
+        (n, eta, w.dot(n))
+    };
+    let sin2_theta_i = (1.0 - cos_theta_i * cos_theta_i).max(0.0);
+    let sin2_theta_t = sin2_theta_i / (eta * eta);
+    if sin2_theta_t >= 1.0 {
+        return None;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+    Some(-w / eta + (cos_theta_i / eta - cos_theta_t) * n)
+}
+
+/// The exact Fresnel reflectance of an interface between two dielectrics,
+/// where `eta` is the ratio of the refractive index on the other side of the
+/// interface over the refractive index on the side of the incoming light.
+pub fn fresnel_dielectric(cos_theta_i: f32, eta: f32) -> f32 {
+    let cos_theta_i = cos_theta_i.clamp(-1.0, 1.0);
+    let (eta, cos_theta_i) = if cos_theta_i < 0.0 {
+        (1.0 / eta, -cos_theta_i)
+    } else {
+        (eta, cos_theta_i)
+    };
+    let sin2_theta_i = 1.0 - cos_theta_i * cos_theta_i;
+    let sin2_theta_t = sin2_theta_i / (eta * eta);
+    if sin2_theta_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).max(0.0).sqrt();
+    let r_parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    0.5 * (r_parallel * r_parallel + r_perpendicular * r_perpendicular)
+}
+
+#[derive(Copy, Clone, Debug)]
+/// A complex number, used for the refractive index of conductors.
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+impl Complex {
+    fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    /// The squared magnitude.
+    fn norm(&self) -> f32 {
+        self.re * self.re + self.im * self.im
+    }
+
+    fn sqrt(&self) -> Self {
+        let n = self.norm().sqrt();
+        if n == 0.0 {
+            return Self::new(0.0, 0.0);
+        }
+        let t1 = (0.5 * (n + self.re.abs())).sqrt();
+        let t2 = 0.5 * self.im / t1;
+        if self.re >= 0.0 {
+            Self::new(t1, t2)
+        } else {
+            Self::new(t2.abs(), t1.copysign(self.im))
+        }
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Mul<Complex> for f32 {
+    type Output = Complex;
+
+    fn mul(self, rhs: Complex) -> Self::Output {
+        Complex::new(self * rhs.re, self * rhs.im)
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        let scale = 1.0 / rhs.norm();
+        Complex::new(
+            scale * (self.re * rhs.re + self.im * rhs.im),
+            scale * (self.im * rhs.re - self.re * rhs.im),
+        )
+    }
+}
+
+/// The Fresnel reflectance of a conductor with the complex refractive index
+/// `eta + i * k` for light arriving at an angle with cosine `cos_theta_i`.
+pub fn fresnel_complex(cos_theta_i: f32, eta: f32, k: f32) -> f32 {
+    let cos_theta_i = Complex::new(cos_theta_i.clamp(0.0, 1.0), 0.0);
+    let eta = Complex::new(eta, k);
+    let one = Complex::new(1.0, 0.0);
+    let sin2_theta_i = one - cos_theta_i * cos_theta_i;
+    let sin2_theta_t = sin2_theta_i / (eta * eta);
+    let cos_theta_t = (one - sin2_theta_t).sqrt();
+
+    let r_parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    0.5 * (r_parallel.norm() + r_perpendicular.norm())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{vec3::Vec3, PI};
+
+    use super::{fresnel_complex, fresnel_dielectric, TrowbridgeReitz};
+
+    #[test]
+    fn fresnel_reflectance() {
+        // At normal incidence, the reflectance is ((eta - 1) / (eta + 1))^2.
+        let r = fresnel_complex(1.0, 1.5, 0.0);
+        assert!((r - 0.04).abs() < 1e-5);
+        // A conductor with a large k reflects nearly everything.
+        assert!(fresnel_complex(1.0, 0.2, 8.0) > 0.98);
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-5);
+        // Total internal reflection inside of glass.
+        assert_eq!(fresnel_dielectric(0.2, 1.0 / 1.5), 1.0);
+    }
+
+    #[test]
+    fn ggx_normalization() {
+        // The projected area of the microfacets equals the area of the surface.
+        let distribution = TrowbridgeReitz::from_roughness(0.5);
+        let n = 400;
+        let mut integral = 0.0;
+        for i in 0..n {
+            let cos_theta = (i as f32 + 0.5) / n as f32;
+            let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+            let wm = Vec3::new(sin_theta, 0.0, cos_theta);
+            integral += distribution.d(wm) * cos_theta * 2.0 * std::f32::consts::PI / n as f32;
+        }
+        assert!((integral - 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn dielectric_pdf_matches_sampling() {
+        // The density integrates to the probability that sampling succeeds.
+        let distribution = TrowbridgeReitz::from_roughness(0.6);
+        let wo = *Vec3::new(0.6, 0.0, 0.8).unit();
+        for eta in [1.5, 1.0 / 1.5] {
+            let n = 200_000;
+            let sampled = (0..n)
+                .filter(|_| distribution.sample_dielectric(wo, eta).is_some())
+                .count() as f32
+                / n as f32;
+            // Integrate by the midpoint rule over the polar and azimuthal
+            // angles, which is steadier than random directions for the
+            // narrow peak of the refraction.
+            let steps = 400;
+            let (d_theta, d_phi) = (PI / steps as f32, 2.0 * PI / steps as f32);
+            let mut integral = 0.0;
+            for i in 0..steps {
+                let theta = (i as f32 + 0.5) * d_theta;
+                for j in 0..steps {
+                    let phi = (j as f32 + 0.5) * d_phi;
+                    let wi = Vec3::new(
+                        theta.sin() * phi.cos(),
+                        theta.sin() * phi.sin(),
+                        theta.cos(),
+                    );
+                    integral += distribution.pdf_dielectric(wo, wi, eta) * theta.sin();
+                }
+            }
+            integral *= d_theta * d_phi;
+            assert!((integral - sampled).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn dielectric_eval_matches_sampling() {
+        // The weight of each sample is the value over the density.
+        let distribution = TrowbridgeReitz::from_roughness(0.4);
+        let wo = *Vec3::new(0.3, 0.4, 0.866).unit();
+        for eta in [1.5, 1.0 / 1.5] {
+            for _ in 0..1000 {
+                let Some((wi, weight)) = distribution.sample_dielectric(wo, eta) else {
+                    continue;
+                };
+                let value = distribution.eval_dielectric(wo, wi, eta);
+                let pdf = distribution.pdf_dielectric(wo, wi, eta);
+                assert!(pdf > 0.0);
+                assert!((value / pdf - weight).abs() < 1e-2 * weight.max(1.0));
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! This module implements an orthonormal basis [ONB], which is used to
+//! transform directions between world space and a local frame around a
+//! surface normal.
+
+use crate::vec3::{Unit3, Vec3};
+
+#[derive(Copy, Clone, Debug)]
+/// An orthonormal basis of three unit vectors `u`, `v`, and `w`. The local
+/// frame of a surface has its normal as `w` and its tangent as `u`.
+pub struct ONB {
+    u: Unit3,
+    v: Unit3,
+    w: Unit3,
+}
+
+impl ONB {
+    /// Create an arbitrary orthonormal basis with `w` as its third vector.
+    pub fn new(w: Unit3) -> Self {
+        // Branchless construction by Duff et al., "Building an Orthonormal
+        // Basis, Revisited".
+        let sign = 1.0_f32.copysign(w.z());
+        let a = -1.0 / (sign + w.z());
+        let b = w.x() * w.y() * a;
+        let u = Vec3::new(1.0 + sign * w.x() * w.x() * a, sign * b, -sign * w.x());
+        let v = Vec3::new(b, sign + w.y() * w.y() * a, -w.y());
+        // SAFETY: The construction yields unit vectors for a unit `w`.
+        Self {
+            u: Unit3::new_unchecked(u),
+            v: Unit3::new_unchecked(v),
+            w,
+        }
+    }
+
+    /// Create an orthonormal basis with `w` as its third vector and `u` as
+    /// close as possible to `tangent`. Falls back to an arbitrary basis if the
+    /// tangent is parallel to `w`.
+    pub fn from_tangent(w: Unit3, tangent: Vec3) -> Self {
+        let u = tangent - tangent.dot(*w) * *w;
+        if u.near_zero() {
+            return Self::new(w);
+        }
+        let u = u.unit();
+        let v = Unit3::new_unchecked(w.cross(*u));
+        Self { u, v, w }
+    }
+
+    #[inline]
+    /// Return the first vector of the basis.
+    pub fn u(&self) -> Unit3 {
+        self.u
+    }
+
+    #[inline]
+    /// Return the second vector of the basis.
+    pub fn v(&self) -> Unit3 {
+        self.v
+    }
+
+    #[inline]
+    /// Return the third vector of the basis.
+    pub fn w(&self) -> Unit3 {
+        self.w
+    }
+
+    #[inline]
+    /// Transform `local` from the coordinates of this basis to world space.
+    pub fn to_world(&self, local: Vec3) -> Vec3 {
+        local.x() * *self.u + local.y() * *self.v + local.z() * *self.w
+    }
+
+    #[inline]
+    /// Transform `world` from world space to the coordinates of this basis.
+    pub fn to_local(&self, world: Vec3) -> Vec3 {
+        Vec3::new(world.dot(*self.u), world.dot(*self.v), world.dot(*self.w))
+    }
+}
@@ -0,0 +1,637 @@
+//! This module contains the principled BSDF by Burley, "Physically-Based
+//! Shading at Disney", which combines diffuse, metallic, glossy, and glass
+//! surfaces in a single [Material] with intuitive parameters, see
+//! [Principled]. Principled materials are created with a
+//! [PrincipledBuilder].
+
+use std::sync::Arc;
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::Material,
+    microfacet::{self, TrowbridgeReitz},
+    onb::ONB,
+    point::Point,
+    random_0_1_f32, random_cosine_direction,
+    ray::Ray,
+    texture::{SolidColor, Texture},
+    vec3::Vec3,
+    PI,
+};
+
+#[derive(Debug, Clone)]
+/// The principled BSDF. It consists of four lobes:
+///
+/// * a diffuse lobe with retro-reflection at rough surfaces and sheen at
+///   grazing angles, which fades out for metals and glass,
+/// * a specular lobe whose microfacets follow the (anisotropic)
+///   Trowbridge-Reitz distribution,
+/// * a clearcoat lobe, which is a second, glossier and white specular lobe,
+/// * a transmission lobe, which refracts through rough glass.
+///
+/// Each scatter picks one of the lobes at random proportional to its weight.
+/// All parameters are given by textures. Scalar parameters are the luminance
+/// of their texture.
+pub struct Principled {
+    base_color: Arc<dyn Texture>,
+    metallic: Arc<dyn Texture>,
+    roughness: Arc<dyn Texture>,
+    specular: Arc<dyn Texture>,
+    specular_tint: Arc<dyn Texture>,
+    sheen: Arc<dyn Texture>,
+    sheen_tint: Arc<dyn Texture>,
+    clearcoat: Arc<dyn Texture>,
+    clearcoat_gloss: Arc<dyn Texture>,
+    transmission: Arc<dyn Texture>,
+    anisotropic: Arc<dyn Texture>,
+    /// The refractive index of the transmission lobe.
+    refraction_index: f32,
+}
+
+/// The parameters of a [Principled] material evaluated at a hit.
+struct Parameters {
+    base_color: Color,
+    metallic: f32,
+    roughness: f32,
+    specular: f32,
+    specular_tint: f32,
+    sheen: f32,
+    sheen_tint: f32,
+    clearcoat: f32,
+    clearcoat_gloss: f32,
+    transmission: f32,
+    anisotropic: f32,
+}
+
+/// The lobes of a [Principled] material.
+#[derive(Clone, Copy)]
+enum Lobe {
+    Diffuse,
+    Specular,
+    Clearcoat,
+    Transmission,
+}
+
+impl Principled {
+    /// Evaluate all parameters at the texture coordinates `u`, `v` and `p`.
+    fn parameters(&self, u: f32, v: f32, p: Point) -> Parameters {
+        let scalar =
+            |texture: &Arc<dyn Texture>| texture.value(u, v, p).luminance().clamp(0.0, 1.0);
+        Parameters {
+            base_color: self.base_color.value(u, v, p),
+            metallic: scalar(&self.metallic),
+            roughness: scalar(&self.roughness),
+            specular: scalar(&self.specular),
+            specular_tint: scalar(&self.specular_tint),
+            sheen: scalar(&self.sheen),
+            sheen_tint: scalar(&self.sheen_tint),
+            clearcoat: scalar(&self.clearcoat),
+            clearcoat_gloss: scalar(&self.clearcoat_gloss),
+            transmission: scalar(&self.transmission),
+            anisotropic: scalar(&self.anisotropic),
+        }
+    }
+
+    /// The lobes of the material with their weights. Rays inside of the
+    /// surface can only be transmitted or reflected back inside.
+    fn lobes(parameters: &Parameters, front_face: bool) -> [(Lobe, f32); 4] {
+        if front_face {
+            let dielectric = 1.0 - parameters.metallic;
+            [
+                (Lobe::Diffuse, dielectric * (1.0 - parameters.transmission)),
+                (Lobe::Specular, 1.0 - dielectric * parameters.transmission),
+                (Lobe::Clearcoat, 0.25 * parameters.clearcoat),
+                (Lobe::Transmission, dielectric * parameters.transmission),
+            ]
+        } else {
+            [
+                (Lobe::Diffuse, 0.0),
+                (Lobe::Specular, 0.0),
+                (Lobe::Clearcoat, 0.0),
+                (Lobe::Transmission, 1.0),
+            ]
+        }
+    }
+
+    /// The distribution of the specular and transmission lobes. Anisotropy
+    /// stretches the highlight along the tangent.
+    fn distribution(parameters: &Parameters) -> TrowbridgeReitz {
+        let alpha = parameters.roughness * parameters.roughness;
+        let aspect = (1.0 - 0.9 * parameters.anisotropic).sqrt();
+        TrowbridgeReitz::new(alpha / aspect, alpha * aspect)
+    }
+
+    /// The distribution of the clearcoat lobe.
+    fn clearcoat_distribution(parameters: &Parameters) -> TrowbridgeReitz {
+        let alpha = 0.1 + (0.001 - 0.1) * parameters.clearcoat_gloss;
+        TrowbridgeReitz::new(alpha, alpha)
+    }
+
+    /// The normal reflectance of the specular lobe, which is tinted by the
+    /// base color for metals.
+    fn specular_f0(parameters: &Parameters) -> Color {
+        let dielectric_f0 = mix(
+            Color::white(),
+            tint(parameters.base_color),
+            parameters.specular_tint,
+        ) * (0.08 * parameters.specular);
+        mix(dielectric_f0, parameters.base_color, parameters.metallic)
+    }
+
+    /// The diffuse lobe including sheen from `wo` into `wi` times `PI`.
+    fn diffuse(parameters: &Parameters, wo: Vec3, wi: Vec3) -> Option<Color> {
+        let wh = wi + wo;
+        if wh.near_zero() {
+            return None;
+        }
+        let cos_theta_d = wi.dot(*wh.unit());
+
+        // Burley's diffuse model, which adds retro-reflection at grazing
+        // angles on rough surfaces and darkens smooth surfaces there.
+        let fd90 = 0.5 + 2.0 * parameters.roughness * cos_theta_d * cos_theta_d;
+        let fd = (1.0 + (fd90 - 1.0) * schlick_weight(wi.z()))
+            * (1.0 + (fd90 - 1.0) * schlick_weight(wo.z()));
+        let sheen = mix(
+            Color::white(),
+            tint(parameters.base_color),
+            parameters.sheen_tint,
+        ) * (parameters.sheen * schlick_weight(cos_theta_d));
+        Some(parameters.base_color * fd + sheen * PI)
+    }
+
+    /// Sample the diffuse lobe including sheen. Returns the direction and the
+    /// weight `f * cos(theta_i) / pdf` of the sample.
+    fn sample_diffuse(parameters: &Parameters, wo: Vec3) -> Option<(Vec3, Color)> {
+        // The pdf of cosine sampling cancels the cosine and the 1 / PI of the
+        // diffuse lobe.
+        let wi = random_cosine_direction();
+        Some((wi, Self::diffuse(parameters, wo, wi)?))
+    }
+
+    /// Sample a visible microfacet of `distribution` and reflect about it.
+    /// Returns the direction and the weight `f * cos(theta_i) / pdf` of the
+    /// sample for the Schlick Fresnel term with normal reflectance `f0`.
+    fn sample_specular(
+        distribution: TrowbridgeReitz,
+        f0: Color,
+        wo: Vec3,
+    ) -> Option<(Vec3, Color)> {
+        if distribution.effectively_smooth() {
+            let wi = Vec3::new(-wo.x(), -wo.y(), wo.z());
+            return Some((wi, schlick(f0, wo.z())));
+        }
+        let wm = distribution.sample_wm(wo);
+        let wi = microfacet::reflect(wo, wm);
+        if wi.z() <= 0.0 {
+            return None;
+        }
+        let masking = distribution.g(wo, wi) / distribution.g1(wo);
+        Some((wi, schlick(f0, wo.dot(wm)) * masking))
+    }
+
+    /// Sample the transmission lobe like [RoughDielectric](crate::material::RoughDielectric).
+    /// Light that enters the surface is tinted by the base color.
+    fn sample_transmission(
+        &self,
+        parameters: &Parameters,
+        distribution: TrowbridgeReitz,
+        front_face: bool,
+        wo: Vec3,
+    ) -> Option<(Vec3, Color)> {
+        let eta = if front_face {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        };
+        let (wi, weight) = distribution.sample_dielectric(wo, eta)?;
+        Some((
+            wi,
+            Self::transmission_tint(parameters, front_face, wi) * weight,
+        ))
+    }
+
+    /// Evaluate the transmission lobe for light that arrives from `wi` like
+    /// [Principled::sample_transmission] scatters it.
+    fn eval_transmission(
+        &self,
+        parameters: &Parameters,
+        distribution: TrowbridgeReitz,
+        front_face: bool,
+        wo: Vec3,
+        wi: Vec3,
+    ) -> Color {
+        let eta = if front_face {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        };
+        Self::transmission_tint(parameters, front_face, wi)
+            * distribution.eval_dielectric(wo, wi, eta)
+    }
+
+    /// The tint of the transmission lobe, which tints light that enters the
+    /// surface by the base color.
+    fn transmission_tint(parameters: &Parameters, front_face: bool, wi: Vec3) -> Color {
+        if wi.z() < 0.0 && front_face {
+            parameters.base_color
+        } else {
+            Color::white()
+        }
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        let parameters = self.parameters(hit_record.u(), hit_record.v(), hit_record.p());
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let distribution = Self::distribution(&parameters);
+        let lobes = Self::lobes(&parameters, hit_record.front_face());
+
+        // Choose a lobe proportional to its weight. Dividing by the
+        // probability of the choice multiplies each lobe with the total weight.
+        let total_weight: f32 = lobes.iter().map(|(_, weight)| weight).sum();
+        let mut choice = random_0_1_f32() * total_weight;
+        let lobe = lobes
+            .iter()
+            .find(|(_, weight)| {
+                choice -= weight;
+                *weight > 0.0 && choice < 0.0
+            })
+            .or_else(|| lobes.iter().rev().find(|(_, weight)| *weight > 0.0))
+            .map(|(lobe, _)| *lobe)?;
+
+        let (wi, attenuation) = match lobe {
+            Lobe::Diffuse => Self::sample_diffuse(&parameters, wo)?,
+            Lobe::Specular => {
+                Self::sample_specular(distribution, Self::specular_f0(&parameters), wo)?
+            }
+            Lobe::Clearcoat => {
+                let distribution = Self::clearcoat_distribution(&parameters);
+                Self::sample_specular(distribution, Color::new(0.04, 0.04, 0.04), wo)?
+            }
+            Lobe::Transmission => {
+                self.sample_transmission(&parameters, distribution, hit_record.front_face(), wo)?
+            }
+        };
+
+        let scattered = ray.spawn(hit_record.p(), frame.to_world(wi));
+        Some((scattered, attenuation * total_weight))
+    }
+
+    fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let parameters = self.parameters(hit_record.u(), hit_record.v(), hit_record.p());
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        if wo.z() <= 0.0 {
+            return Color::black();
+        }
+
+        // Sum up the lobes by their weights. Only the transmission lobe
+        // reaches below the surface.
+        let specular =
+            |distribution: TrowbridgeReitz, f0: Color| match distribution.eval_reflection(wo, wi) {
+                Some((wm, value)) => schlick(f0, wo.dot(wm)) * value,
+                None => Color::black(),
+            };
+        let mut result = Color::black();
+        for (lobe, weight) in Self::lobes(&parameters, hit_record.front_face()) {
+            if weight <= 0.0 {
+                continue;
+            }
+            let value = match lobe {
+                Lobe::Diffuse if wi.z() <= 0.0 => Color::black(),
+                Lobe::Diffuse => Self::diffuse(&parameters, wo, wi)
+                    .map_or(Color::black(), |diffuse| diffuse * (wi.z() / PI)),
+                Lobe::Specular => specular(
+                    Self::distribution(&parameters),
+                    Self::specular_f0(&parameters),
+                ),
+                Lobe::Clearcoat => specular(
+                    Self::clearcoat_distribution(&parameters),
+                    Color::new(0.04, 0.04, 0.04),
+                ),
+                Lobe::Transmission => self.eval_transmission(
+                    &parameters,
+                    Self::distribution(&parameters),
+                    hit_record.front_face(),
+                    wo,
+                    wi,
+                ),
+            };
+            result += value * weight;
+        }
+        result
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let parameters = self.parameters(hit_record.u(), hit_record.v(), hit_record.p());
+        let frame = ONB::from_tangent(hit_record.normal(), *hit_record.tangent());
+        let wo = frame.to_local(-*ray.direction().unit());
+        let wi = frame.to_local(*direction.unit());
+        if wo.z() <= 0.0 {
+            return 0.0;
+        }
+
+        // The densities of the lobes, mixed by the probability of choosing
+        // them in [Principled::scatter].
+        let lobes = Self::lobes(&parameters, hit_record.front_face());
+        let total_weight: f32 = lobes.iter().map(|(_, weight)| weight).sum();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        let mut pdf = 0.0;
+        for (lobe, weight) in lobes {
+            if weight <= 0.0 {
+                continue;
+            }
+            // A smooth lobe scatters into single directions, which no density
+            // describes, so the mixture has none either.
+            let smooth = match lobe {
+                Lobe::Diffuse => false,
+                Lobe::Specular | Lobe::Transmission => {
+                    Self::distribution(&parameters).effectively_smooth()
+                }
+                Lobe::Clearcoat => Self::clearcoat_distribution(&parameters).effectively_smooth(),
+            };
+            if smooth {
+                return 0.0;
+            }
+            let density = match lobe {
+                Lobe::Diffuse => wi.z().max(0.0) / PI,
+                Lobe::Specular => Self::distribution(&parameters).pdf_reflection(wo, wi),
+                Lobe::Clearcoat => Self::clearcoat_distribution(&parameters).pdf_reflection(wo, wi),
+                Lobe::Transmission => {
+                    let eta = if hit_record.front_face() {
+                        self.refraction_index
+                    } else {
+                        1.0 / self.refraction_index
+                    };
+                    Self::distribution(&parameters).pdf_dielectric(wo, wi, eta)
+                }
+            };
+            pdf += density * weight / total_weight;
+        }
+        pdf
+    }
+}
+
+/// The weight `(1 - cos(theta))^5` of the Schlick approximation.
+fn schlick_weight(cos_theta: f32) -> f32 {
+    (1.0 - cos_theta).clamp(0.0, 1.0).powi(5)
+}
+
+/// The Schlick approximation of the Fresnel reflectance with normal
+/// reflectance `f0`.
+fn schlick(f0: Color, cos_theta: f32) -> Color {
+    mix(f0, Color::white(), schlick_weight(cos_theta))
+}
+
+/// Linearly interpolate from `a` to `b` by `t`.
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    a * (1.0 - t) + b * t
+}
+
+/// The hue and saturation of `color` with luminance 1.
+fn tint(color: Color) -> Color {
+    let luminance = color.luminance();
+    if luminance > 0.0 {
+        color * (1.0 / luminance)
+    } else {
+        Color::white()
+    }
+}
+
+/// Generate a pair of setters for a scalar parameter of the builder, one for
+/// a constant and one for a texture.
+macro_rules! scalar_parameter {
+    ($name:ident, $texture_name:ident, $doc:literal) => {
+        #[doc = concat!("Set the ", $doc, " in `[0, 1]`.")]
+        pub fn $name(&mut self, $name: f32) -> &mut Self {
+            self.$name = Arc::new(SolidColor::from_rbg($name, $name, $name));
+            self
+        }
+
+        #[doc = concat!("Set the ", $doc, " by a texture.")]
+        pub fn $texture_name(&mut self, texture: Arc<dyn Texture>) -> &mut Self {
+            self.$name = texture;
+            self
+        }
+    };
+}
+
+/// A builder for [Principled] materials. All parameters default to a white,
+/// rough, dielectric surface.
+#[derive(Debug, Clone)]
+pub struct PrincipledBuilder {
+    base_color: Arc<dyn Texture>,
+    metallic: Arc<dyn Texture>,
+    roughness: Arc<dyn Texture>,
+    specular: Arc<dyn Texture>,
+    specular_tint: Arc<dyn Texture>,
+    sheen: Arc<dyn Texture>,
+    sheen_tint: Arc<dyn Texture>,
+    clearcoat: Arc<dyn Texture>,
+    clearcoat_gloss: Arc<dyn Texture>,
+    transmission: Arc<dyn Texture>,
+    anisotropic: Arc<dyn Texture>,
+    refraction_index: f32,
+}
+
+impl PrincipledBuilder {
+    /// Construct a new, default builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a [Principled] material from this builder.
+    pub fn build(&self) -> Principled {
+        Principled {
+            base_color: self.base_color.clone(),
+            metallic: self.metallic.clone(),
+            roughness: self.roughness.clone(),
+            specular: self.specular.clone(),
+            specular_tint: self.specular_tint.clone(),
+            sheen: self.sheen.clone(),
+            sheen_tint: self.sheen_tint.clone(),
+            clearcoat: self.clearcoat.clone(),
+            clearcoat_gloss: self.clearcoat_gloss.clone(),
+            transmission: self.transmission.clone(),
+            anisotropic: self.anisotropic.clone(),
+            refraction_index: self.refraction_index,
+        }
+    }
+
+    /// Set the base color, i.e., the diffuse color of dielectrics and the
+    /// specular color of metals.
+    pub fn base_color(&mut self, base_color: Color) -> &mut Self {
+        self.base_color = Arc::new(SolidColor::new(base_color));
+        self
+    }
+
+    /// Set the base color by a texture.
+    pub fn base_color_texture(&mut self, texture: Arc<dyn Texture>) -> &mut Self {
+        self.base_color = texture;
+        self
+    }
+
+    scalar_parameter!(
+        metallic,
+        metallic_texture,
+        "blend from a dielectric (0) to a metal (1)"
+    );
+    scalar_parameter!(
+        roughness,
+        roughness_texture,
+        "roughness of the specular and the diffuse lobe"
+    );
+    scalar_parameter!(
+        specular,
+        specular_texture,
+        "strength of the specular reflection of dielectrics, where 0.5 is a common 4% reflectance"
+    );
+    scalar_parameter!(
+        specular_tint,
+        specular_tint_texture,
+        "blend of the specular reflection of dielectrics from white to the base color"
+    );
+    scalar_parameter!(
+        sheen,
+        sheen_texture,
+        "strength of the sheen at grazing angles, e.g., for cloth"
+    );
+    scalar_parameter!(
+        sheen_tint,
+        sheen_tint_texture,
+        "blend of the sheen from white to the base color"
+    );
+    scalar_parameter!(
+        clearcoat,
+        clearcoat_texture,
+        "strength of the white clearcoat lobe"
+    );
+    scalar_parameter!(
+        clearcoat_gloss,
+        clearcoat_gloss_texture,
+        "glossiness of the clearcoat from satin (0) to gloss (1)"
+    );
+    scalar_parameter!(
+        transmission,
+        transmission_texture,
+        "blend from an opaque (0) to a transmissive (1) dielectric"
+    );
+    scalar_parameter!(
+        anisotropic,
+        anisotropic_texture,
+        "anisotropy of the specular lobe, which stretches the highlight along the tangent"
+    );
+
+    /// Set the refractive index of the transmission lobe.
+    pub fn refraction_index(&mut self, refraction_index: f32) -> &mut Self {
+        self.refraction_index = refraction_index;
+        self
+    }
+}
+
+impl Default for PrincipledBuilder {
+    fn default() -> Self {
+        let constant = |value: f32| -> Arc<dyn Texture> {
+            Arc::new(SolidColor::from_rbg(value, value, value))
+        };
+        Self {
+            base_color: constant(0.8),
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            specular_tint: constant(0.0),
+            sheen: constant(0.0),
+            sheen_tint: constant(0.5),
+            clearcoat: constant(0.0),
+            clearcoat_gloss: constant(1.0),
+            transmission: constant(0.0),
+            anisotropic: constant(0.0),
+            refraction_index: 1.5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        color::Color, hittable::HitRecord, material::Material, point::Point, ray::Ray, vec3::Vec3,
+    };
+
+    use super::PrincipledBuilder;
+
+    #[test]
+    fn smooth_white_metal_is_a_mirror() {
+        let material = Arc::new(
+            PrincipledBuilder::new()
+                .base_color(Color::white())
+                .metallic(1.0)
+                .roughness(0.0)
+                .build(),
+        );
+        let ray = Ray::new(Point::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), 0.0);
+        let hit_record = HitRecord::new(
+            &ray,
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0).unit(),
+            1.0,
+            0.0,
+            0.0,
+            material.clone(),
+        );
+        let (scattered, attenuation) = material
+            .scatter(&ray, hit_record)
+            .expect("The metal reflects.");
+        let direction = scattered.direction().unit();
+        assert!((direction.x() - direction.y()).abs() < 1e-4 && direction.x() > 0.0);
+        assert!((attenuation.r() - 1.0).abs() < 1e-4);
+        assert!((attenuation.b() - 1.0).abs() < 1e-4);
+
+        // Rough glass reflects and transmits light, which both are weighed
+        // by the value over the density of the direction.
+        let glass = Arc::new(
+            PrincipledBuilder::new()
+                .base_color(Color::new(1.0, 0.5, 0.25))
+                .roughness(0.5)
+                .transmission(1.0)
+                .build(),
+        );
+        let hit_record = HitRecord::new(
+            &ray,
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0).unit(),
+            1.0,
+            0.0,
+            0.0,
+            glass.clone(),
+        );
+        let (mut reflected, mut transmitted) = (0, 0);
+        for _ in 0..1000 {
+            let Some((scattered, attenuation)) = glass.scatter(&ray, hit_record.copy()) else {
+                continue;
+            };
+            let direction = *scattered.direction();
+            if direction.y() > 0.0 {
+                reflected += 1;
+            } else {
+                transmitted += 1;
+            }
+            let value = glass.eval(&ray, &hit_record, direction);
+            let pdf = glass.pdf(&ray, &hit_record, direction);
+            assert!(pdf > 0.0);
+            assert!((value.b() / pdf - attenuation.b()).abs() < 1e-2 * attenuation.b().max(1.0));
+        }
+        assert!(reflected > 0 && transmitted > 0);
+    }
+}
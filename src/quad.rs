@@ -4,13 +4,23 @@ use std::sync::Arc;
 
 use crate::{
     aabb::AABB,
+    color::Color,
     hittable::{HitRecord, Hittable, World},
     interval::Interval,
+    light::Light,
+    light_bvh::LightBounds,
     material::Material,
     point::Point,
+    random_0_1_f32,
+    ray::Ray,
     vec3::{Unit3, Vec3},
+    INFINITY, PI,
 };
 
+/// The number of points along each side of the grid over which a quad light
+/// estimates its power.
+const POWER_GRID: u32 = 4;
+
 #[derive(Debug, Clone)]
 /// This struct implements a general quadrilateral.
 pub struct Quad {
@@ -122,7 +132,7 @@ impl Quad {
 }
 
 impl Hittable for Quad {
-    fn hit(&self, ray: &crate::ray::Ray, ray_t: Interval) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
         let denom = self.normal.dot(*ray.direction());
 
         // No thit if the ray is parallel to the plane.
@@ -144,7 +154,7 @@ impl Hittable for Quad {
             return None;
         }
 
-        Some(HitRecord::new(
+        let mut hit_record = HitRecord::new(
             ray,
             intersection,
             self.normal,
@@ -152,7 +162,9 @@ impl Hittable for Quad {
             alpha,
             beta,
             self.material.clone(),
-        ))
+        );
+        hit_record.set_partials(self.u, self.v);
+        Some(hit_record)
     }
 
     fn bounding_box(&self) -> &AABB {
@@ -160,13 +172,99 @@ impl Hittable for Quad {
     }
 }
 
+/// A quad with an emissive material, e.g., a
+/// [DiffuseLight](crate::material::DiffuseLight), is an area light when it is
+/// pushed to the world as both an object and a light.
+impl Light for Quad {
+    fn sample(&self, p: Point) -> Option<(Vec3, f32, Color)> {
+        let target = self.q + random_0_1_f32() * self.u + random_0_1_f32() * self.v;
+        let ray = Ray::new(p, target - p, 0.0);
+        let hit_record = self.hit(&ray, Interval::new(0.0, INFINITY))?;
+        let radiance = self.material.emitted(&ray, &hit_record);
+        let distance = (target - p).length();
+        let direction = (target - p) / distance;
+        let pdf = self.pdf(p, direction);
+        if pdf <= 0.0 || radiance.luminance() <= 0.0 {
+            return None;
+        }
+        Some((direction, distance, radiance * (1.0 / pdf)))
+    }
+
+    fn pdf(&self, p: Point, direction: Vec3) -> f32 {
+        let direction = direction.unit();
+        let ray = Ray::new(p, *direction, 0.0);
+        let Some(hit_record) = self.hit(&ray, Interval::new(0.0, INFINITY)) else {
+            return 0.0;
+        };
+        // Convert the density with respect to the area to the solid angle.
+        let area = self.u.cross(self.v).length();
+        let cos_theta = direction.dot(*self.normal).abs();
+        hit_record.t() * hit_record.t() / (cos_theta * area)
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        if (self.normal.dot(*point) - self.d).abs() > 1e-3 {
+            return false;
+        }
+        let planar_hitpt_vector = point - self.q;
+        let alpha = self.w.dot(planar_hitpt_vector.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar_hitpt_vector));
+        Self::is_interior(alpha, beta)
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        // Estimate the radiance of each side by the average light leaving a
+        // grid of points, so that a textured light which is dark in places
+        // is not missed.
+        let radiance = |side: f32| {
+            let mut total = 0.0;
+            for i in 0..POWER_GRID {
+                for j in 0..POWER_GRID {
+                    let alpha = (i as f32 + 0.5) / POWER_GRID as f32;
+                    let beta = (j as f32 + 0.5) / POWER_GRID as f32;
+                    let target = self.q + alpha * self.u + beta * self.v;
+                    let origin = target + side * *self.normal;
+                    let ray = Ray::new(origin, target - origin, 0.0);
+                    total += self
+                        .hit(&ray, Interval::new(0.0, INFINITY))
+                        .map_or(0.0, |hit_record| {
+                            self.material.emitted(&ray, &hit_record).luminance()
+                        });
+                }
+            }
+            total / (POWER_GRID * POWER_GRID) as f32
+        };
+        let (front, back) = (radiance(1.0), radiance(-1.0));
+        let axis = if front > 0.0 {
+            *self.normal
+        } else {
+            -*self.normal
+        };
+        let power = PI * self.u.cross(self.v).length() * (front + back);
+        Some(LightBounds::new(
+            self.bounding_box,
+            axis,
+            1.0,
+            0.0,
+            power,
+            front > 0.0 && back > 0.0,
+        ))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
 
     use crate::{
-        color::Color, hittable::Hittable, interval::Interval, material::Lambertian, point::Point,
-        ray::Ray, vec3::Vec3,
+        color::Color,
+        hittable::{hit_opaque, Hittable},
+        interval::Interval,
+        material::{AlphaMask, Coated, Lambertian, MixMaterial},
+        point::Point,
+        ray::Ray,
+        texture::SolidColor,
+        vec3::Vec3,
     };
 
     use super::Quad;
@@ -184,4 +282,40 @@ mod test {
         let hit = q.hit(&r, Interval::universe());
         assert!(hit.is_some());
     }
+
+    #[test]
+    fn skip_transparent_quad() {
+        let transparent = AlphaMask::new(
+            Arc::new(Lambertian::new(Color::new(0.2, 1.0, 0.2))),
+            Arc::new(SolidColor::new(Color::black())),
+        );
+        let q = Quad::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Arc::new(transparent),
+        );
+        let r = Ray::new(Point::new(1.0, 1.0, 9.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(q.hit(&r, Interval::universe()).is_some());
+        assert!(hit_opaque(&q, &r, Interval::universe()).is_none());
+    }
+
+    #[test]
+    fn skip_nested_transparent_quad() {
+        let transparent = Arc::new(AlphaMask::new(
+            Arc::new(Lambertian::new(Color::new(0.2, 1.0, 0.2))),
+            Arc::new(SolidColor::new(Color::black())),
+        ));
+        let coated = Arc::new(Coated::new(transparent.clone(), 1.5, 0.0));
+        let mixed = MixMaterial::from_weight(transparent, coated, 0.5);
+        let q = Quad::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Arc::new(mixed),
+        );
+        let r = Ray::new(Point::new(1.0, 1.0, 9.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(q.hit(&r, Interval::universe()).is_some());
+        assert!(hit_opaque(&q, &r, Interval::universe()).is_none());
+    }
 }
@@ -9,6 +9,9 @@ pub struct Ray {
     origin: Point,
     direction: Vec3,
     time: f32,
+    /// The wavelength in nanometers of the light the ray carries. Rays carry
+    /// RGB colors if this is `None`.
+    wavelength: Option<f32>,
 }
 
 impl Ray {
@@ -18,9 +21,22 @@ impl Ray {
             origin,
             direction,
             time,
+            wavelength: None,
         }
     }
 
+    /// Set the wavelength in nanometers of the light the ray carries.
+    pub fn with_wavelength(mut self, wavelength: Option<f32>) -> Self {
+        self.wavelength = wavelength;
+        self
+    }
+
+    /// Create a new ray from `origin` in `direction` that continues this ray,
+    /// i.e., it has the same time and wavelength.
+    pub fn spawn(&self, origin: Point, direction: Vec3) -> Self {
+        Self::new(origin, direction, self.time).with_wavelength(self.wavelength)
+    }
+
     #[inline]
     /// Get the origin of the point.
     pub fn origin(&self) -> &Point {
@@ -39,6 +55,12 @@ impl Ray {
         self.time
     }
 
+    #[inline]
+    /// Get the wavelength in nanometers of the light the ray carries, if any.
+    pub fn wavelength(&self) -> Option<f32> {
+        self.wavelength
+    }
+
     #[inline]
     /// Compute `ray.origin + t * ray.direction`. I.e., follow the direction of
     /// the ray from the origin scaled by `t`.
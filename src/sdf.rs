@@ -0,0 +1,414 @@
+//! This module contains the code for shapes defined by signed distance
+//! functions (SDFs). A signed distance function returns the distance from a
+//! point to the surface of the shape, which is negative inside of the shape.
+//! Such shapes are rendered by sphere tracing, see [SdfShape].
+
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{
+    aabb::AABB,
+    hittable::{HitRecord, Hittable, Sphere},
+    interval::Interval,
+    material::Material,
+    point::Point,
+    ray::Ray,
+    vec3::{Unit3, Vec3},
+};
+
+/// The maximum number of steps taken along a ray before giving up.
+const MAX_STEPS: u32 = 512;
+
+/// The distance to the surface at which a point counts as being on the surface.
+const SURFACE_EPSILON: f32 = 0.0001;
+
+/// The offset used to estimate the gradient of the distance function.
+const NORMAL_EPSILON: f32 = 0.0001;
+
+/// A trait that defines the behavior of signed distance functions.
+///
+/// The returned distance is allowed to underestimate the true distance to the
+/// surface, but it must never overestimate it. Otherwise, sphere tracing can
+/// step through the surface.
+pub trait Sdf: Debug + Send + Sync {
+    /// Compute the signed distance from `p` to the surface.
+    fn distance(&self, p: Point) -> f32;
+}
+
+#[derive(Debug, Clone)]
+/// A [Hittable] defined by a signed distance function which is rendered by
+/// sphere tracing. The shape must lie inside of the given bounding box.
+pub struct SdfShape {
+    sdf: Arc<dyn Sdf>,
+    material: Arc<dyn Material>,
+    bounding_box: AABB,
+}
+
+impl SdfShape {
+    /// Create a new shape from a signed distance function.
+    ///
+    /// * `sdf` - The signed distance function that defines the shape.
+    /// * `bounding_box` - A bounding box which contains the whole shape.
+    /// * `material` - The surface material of the shape.
+    pub fn new(sdf: Arc<dyn Sdf>, bounding_box: AABB, material: Arc<dyn Material>) -> Self {
+        Self {
+            sdf,
+            material,
+            bounding_box,
+        }
+    }
+
+    /// Estimate the outward surface normal at `p` by central differences.
+    fn normal(&self, p: Point) -> Unit3 {
+        let dx = Vec3::new(NORMAL_EPSILON, 0.0, 0.0);
+        let dy = Vec3::new(0.0, NORMAL_EPSILON, 0.0);
+        let dz = Vec3::new(0.0, 0.0, NORMAL_EPSILON);
+        let gradient = Vec3::new(
+            self.sdf.distance(p + dx) - self.sdf.distance(p - dx),
+            self.sdf.distance(p + dy) - self.sdf.distance(p - dy),
+            self.sdf.distance(p + dz) - self.sdf.distance(p - dz),
+        );
+        gradient.unit()
+    }
+}
+
+impl Hittable for SdfShape {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let box_t = self.bounding_box.hit(ray, ray_t)?;
+        let ray_length = ray.direction().length();
+        // The surface may touch the bounding box, so allow to march slightly
+        // beyond it.
+        let t_max = box_t.max() + SURFACE_EPSILON / ray_length;
+
+        // March on the side of the surface the ray starts on. This allows
+        // rays to leave the shape again, e.g., for dielectric materials.
+        let mut t = box_t.min();
+        let mut side = self.sdf.distance(ray.at(t)).signum();
+        for _ in 0..MAX_STEPS {
+            let distance = side * self.sdf.distance(ray.at(t));
+            if distance < SURFACE_EPSILON {
+                // Rays that leave the surface, e.g., scattered rays, start on
+                // it. Step past it and march on, on the side the ray is on
+                // now, to find other parts of the shape.
+                if !ray_t.surrounds(t) {
+                    t += distance.max(SURFACE_EPSILON) / ray_length;
+                    side = self.sdf.distance(ray.at(t)).signum();
+                    continue;
+                }
+                let p = ray.at(t);
+                let normal = self.normal(p);
+                let (u, v) = Sphere::get_sphere_uv(Point::from(*normal));
+                return Some(HitRecord::new(
+                    ray,
+                    p,
+                    normal,
+                    t,
+                    u,
+                    v,
+                    self.material.clone(),
+                ));
+            }
+            t += distance / ray_length;
+            if t > t_max {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self) -> &AABB {
+        &self.bounding_box
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The signed distance function of a sphere.
+pub struct SdfSphere {
+    center: Point,
+    radius: f32,
+}
+
+impl SdfSphere {
+    /// Create a new sphere with the given `center` and `radius`.
+    pub fn new(center: Point, radius: f32) -> Self {
+        Self { center, radius }
+    }
+}
+
+impl Sdf for SdfSphere {
+    fn distance(&self, p: Point) -> f32 {
+        (p - self.center).length() - self.radius
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The signed distance function of a box with rounded edges.
+pub struct RoundedBox {
+    center: Point,
+    half_extents: Vec3,
+    radius: f32,
+}
+
+impl RoundedBox {
+    /// Create a new rounded box.
+    ///
+    /// * `center` - The center of the box.
+    /// * `half_extents` - The distances from the center to the sides of the box.
+    /// * `radius` - The radius by which the edges are rounded. The edges are
+    ///   rounded inwards, i.e., the box does not grow.
+    pub fn new(center: Point, half_extents: Vec3, radius: f32) -> Self {
+        Self {
+            center,
+            half_extents,
+            radius,
+        }
+    }
+}
+
+impl Sdf for RoundedBox {
+    fn distance(&self, p: Point) -> f32 {
+        let p = p - self.center;
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        let q = Vec3::new(p.x().abs(), p.y().abs(), p.z().abs()) - self.half_extents + radius;
+        let outside = Vec3::new(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0)).length();
+        let inside = q.x().max(q.y()).max(q.z()).min(0.0);
+        outside + inside - self.radius
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The signed distance function of a torus that lies in the x and z plane.
+pub struct Torus {
+    center: Point,
+    major_radius: f32,
+    minor_radius: f32,
+}
+
+impl Torus {
+    /// Create a new torus.
+    ///
+    /// * `center` - The center of the torus.
+    /// * `major_radius` - The distance from the center to the center of the tube.
+    /// * `minor_radius` - The radius of the tube.
+    pub fn new(center: Point, major_radius: f32, minor_radius: f32) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Sdf for Torus {
+    fn distance(&self, p: Point) -> f32 {
+        let p = p - self.center;
+        let q = (p.x() * p.x() + p.z() * p.z()).sqrt() - self.major_radius;
+        (q * q + p.y() * p.y()).sqrt() - self.minor_radius
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A distance estimator for the Mandelbulb fractal.
+pub struct Mandelbulb {
+    center: Point,
+    scale: f32,
+    power: f32,
+    iterations: u32,
+}
+
+impl Mandelbulb {
+    /// Create a new Mandelbulb.
+    ///
+    /// * `center` - The center of the fractal.
+    /// * `scale` - The size of the fractal. It has roughly radius `1.2 * scale`.
+    /// * `power` - The power of the iteration. The classic Mandelbulb uses 8.
+    /// * `iterations` - The number of iterations. More iterations add detail.
+    pub fn new(center: Point, scale: f32, power: f32, iterations: u32) -> Self {
+        Self {
+            center,
+            scale,
+            power,
+            iterations,
+        }
+    }
+}
+
+impl Sdf for Mandelbulb {
+    fn distance(&self, p: Point) -> f32 {
+        let c = (p - self.center) / self.scale;
+        let mut z = c;
+        let mut dr = 1.0;
+        let mut r = 0.0;
+        for _ in 0..self.iterations {
+            r = z.length();
+            if r > 2.0 || r == 0.0 {
+                break;
+            }
+            // Convert to polar coordinates, scale and rotate, and convert back.
+            let theta = (z.z() / r).acos() * self.power;
+            let phi = z.y().atan2(z.x()) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+            let zr = r.powf(self.power);
+            z =
+                zr * Vec3::new(
+                    theta.sin() * phi.cos(),
+                    phi.sin() * theta.sin(),
+                    theta.cos(),
+                ) + c;
+        }
+        // The orbit of the center stays at the origin, which is inside.
+        if r == 0.0 {
+            return 0.0;
+        }
+        0.5 * r.ln() * r / dr * self.scale
+    }
+}
+
+#[derive(Debug, Clone)]
+/// The smooth union of two signed distance functions, which blends the shapes
+/// together where they are close.
+pub struct SmoothUnion {
+    a: Arc<dyn Sdf>,
+    b: Arc<dyn Sdf>,
+    k: f32,
+}
+
+impl SmoothUnion {
+    /// Create the smooth union of `a` and `b`. The smoothing distance `k`
+    /// controls how far the blend region reaches.
+    pub fn new(a: Arc<dyn Sdf>, b: Arc<dyn Sdf>, k: f32) -> Self {
+        Self { a, b, k }
+    }
+}
+
+impl Sdf for SmoothUnion {
+    fn distance(&self, p: Point) -> f32 {
+        let d1 = self.a.distance(p);
+        let d2 = self.b.distance(p);
+        let h = (0.5 + 0.5 * (d2 - d1) / self.k).clamp(0.0, 1.0);
+        d2 + (d1 - d2) * h - self.k * h * (1.0 - h)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Infinite repetition of a signed distance function. The space is divided
+/// into cells of size `period` and each cell contains a copy of the shape
+/// centered at the origin.
+pub struct Repetition {
+    sdf: Arc<dyn Sdf>,
+    period: Vec3,
+}
+
+impl Repetition {
+    /// Repeat `sdf` in each cell of size `period`. The shape should fit into a
+    /// single cell.
+    pub fn new(sdf: Arc<dyn Sdf>, period: Vec3) -> Self {
+        Self { sdf, period }
+    }
+}
+
+impl Sdf for Repetition {
+    fn distance(&self, p: Point) -> f32 {
+        let repeat = |x: f32, period: f32| x - period * (x / period).round();
+        let q = Point::new(
+            repeat(p.x(), self.period.x()),
+            repeat(p.y(), self.period.y()),
+            repeat(p.z(), self.period.z()),
+        );
+        self.sdf.distance(q)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A twist of a signed distance function around the Y-axis.
+pub struct Twist {
+    sdf: Arc<dyn Sdf>,
+    amount: f32,
+}
+
+impl Twist {
+    /// Twist `sdf` by `amount` radians per unit along the Y-axis.
+    pub fn new(sdf: Arc<dyn Sdf>, amount: f32) -> Self {
+        Self { sdf, amount }
+    }
+}
+
+impl Sdf for Twist {
+    fn distance(&self, p: Point) -> f32 {
+        let angle = self.amount * p.y();
+        let (sin, cos) = angle.sin_cos();
+        let q = Point::new(cos * p.x() - sin * p.z(), p.y(), sin * p.x() + cos * p.z());
+        // Twisting distorts distances. Scale the distance by a bound on the
+        // distortion at this radius, such that it does not overestimate.
+        let radius = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        self.sdf.distance(q) / (1.0 + self.amount.abs() * radius)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        aabb::AABB, color::Color, hittable::Hittable, interval::Interval, material::Lambertian,
+        point::Point, ray::Ray, vec3::Vec3,
+    };
+
+    use super::{Mandelbulb, Sdf, SdfShape, SdfSphere, Torus};
+
+    #[test]
+    fn hit_sdf_sphere() {
+        let shape = SdfShape::new(
+            Arc::new(SdfSphere::new(Point::new(0.0, 0.0, 0.0), 1.0)),
+            AABB::from_points(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0)),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0), 0.0);
+        let hit = shape
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .expect("The ray hits the sphere.");
+        assert!((hit.t() - 2.0).abs() < 1e-3);
+        assert!(hit.front_face());
+        assert!((hit.normal().z() - 1.0).abs() < 1e-3);
+
+        // The ray leaves the sphere again from the inside.
+        let hit = shape
+            .hit(&ray, Interval::new(hit.t() + 0.001, f32::INFINITY))
+            .expect("The ray exits the sphere.");
+        assert!((hit.t() - 3.0).abs() < 1e-3);
+        assert!(!hit.front_face());
+    }
+
+    #[test]
+    fn ray_leaving_a_torus_hits_its_opposite_side() {
+        let shape = SdfShape::new(
+            Arc::new(Torus::new(Point::new(0.0, 0.0, 0.0), 1.0, 0.25)),
+            AABB::from_points(
+                Point::new(-1.25, -0.25, -1.25),
+                Point::new(1.25, 0.25, 1.25),
+            ),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+
+        // A ray leaves the inner side of the tube through the hole.
+        let ray = Ray::new(Point::new(-0.75, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = shape
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .expect("The ray hits the opposite side of the torus.");
+        assert!((hit.t() - 1.5).abs() < 1e-3);
+        assert!(hit.front_face());
+
+        // A ray refracted into the tube leaves it on its far side.
+        let ray = Ray::new(Point::new(-1.25, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = shape
+            .hit(&ray, Interval::new(0.001, f32::INFINITY))
+            .expect("The ray exits the tube.");
+        assert!((hit.t() - 0.5).abs() < 1e-3);
+        assert!(!hit.front_face());
+    }
+
+    #[test]
+    fn mandelbulb_is_finite_at_its_center() {
+        let bulb = Mandelbulb::new(Point::new(1.0, 2.0, 3.0), 1.0, 8.0, 8);
+        assert!(bulb.distance(Point::new(1.0, 2.0, 3.0)).is_finite());
+    }
+}
@@ -0,0 +1,282 @@
+//! This module contains a procedural model of the daylight sky after Preetham
+//! et al., "A Practical Analytic Model for Daylight". See [Sky].
+
+use image::Rgb32FImage;
+
+use crate::{
+    color::Color,
+    degrees_to_radians,
+    environment::{equirectangular_direction, EnvironmentMap},
+    light::SunLight,
+    spectrum::{spectrum_to_rgb, xyz_to_linear_srgb},
+    vec3::Vec3,
+    PI,
+};
+
+/// The luminance in kcd/m^2 that is mapped to a radiance of 1.
+const KCD_PER_UNIT: f32 = 10.0;
+
+/// The luminance of the sun above the atmosphere in the units of the sky,
+/// i.e., about 2e9 cd/m^2.
+const SUN_RADIANCE: f32 = 2.0e5;
+
+/// The angle in degrees that the sun spans in the sky.
+const SUN_ANGULAR_DIAMETER: f32 = 0.53;
+
+/// The coefficients `A` to `E` of the Perez formula for one of the luminance
+/// and the chromaticities of the sky.
+type Perez = [f32; 5];
+
+#[derive(Debug, Clone, Copy)]
+/// A clear sky lit by the sun. The color of the sky follows the position of
+/// the sun and the turbidity of the atmosphere, i.e., how hazy it is. A sky is
+/// used by surrounding the world with its [EnvironmentMap] and adding its
+/// [SunLight]:
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use ray_tracing_weekend::{hittable::World, sky::Sky};
+/// let mut world = World::new();
+/// let sky = Sky::new(30.0, 120.0, 3.0);
+/// world.set_environment(Arc::new(sky.to_environment(512)));
+/// world.push_light(Arc::new(sky.sun()));
+/// ```
+///
+/// The model ends at the horizon, below which the sky keeps the brightness of
+/// the horizon, so the scene needs a ground. A radiance of 1 corresponds to a
+/// luminance of 10 kcd/m^2, which is about the brightness of the sky straight
+/// up on a clear day.
+pub struct Sky {
+    /// The unit direction towards the sun.
+    sun_direction: Vec3,
+    /// The angle between the sun and the zenith in radians.
+    theta_sun: f32,
+    turbidity: f32,
+    /// The luminance and the chromaticities `x` and `y` at the zenith.
+    zenith: [f32; 3],
+    /// The coefficients of the Perez formula for the luminance and the
+    /// chromaticities.
+    perez: [Perez; 3],
+}
+
+impl Sky {
+    /// Create a new sky.
+    ///
+    /// * `elevation` - The angle of the sun above the horizon in degrees. The
+    ///   model is meant for the sun above the horizon.
+    /// * `azimuth` - The angle of the sun in degrees around the y-axis, which
+    ///   starts at `-z` and turns towards `+x`.
+    /// * `turbidity` - The haziness of the atmosphere, from about 2 for a very
+    ///   clear sky to about 10 for a hazy one.
+    pub fn new(elevation: f32, azimuth: f32, turbidity: f32) -> Self {
+        let elevation = degrees_to_radians(elevation);
+        let azimuth = degrees_to_radians(azimuth);
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+        let theta_sun = (0.5 * PI - elevation).clamp(0.0, 0.5 * PI);
+        let t = turbidity;
+
+        // The zenith luminance in kcd/m^2 and the zenith chromaticities.
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_sun);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let theta = [theta_sun.powi(3), theta_sun.powi(2), theta_sun, 1.0];
+        let polynomial = |c2: [f32; 4], c1: [f32; 4], c0: [f32; 4]| {
+            (0..4)
+                .map(|i| (t * t * c2[i] + t * c1[i] + c0[i]) * theta[i])
+                .sum::<f32>()
+        };
+        let zenith_x = polynomial(
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        );
+        let zenith_y = polynomial(
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        );
+
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        Self {
+            sun_direction,
+            theta_sun,
+            turbidity,
+            zenith: [zenith_luminance, zenith_x, zenith_y],
+            perez,
+        }
+    }
+
+    /// The unit direction towards the sun.
+    pub fn sun_direction(&self) -> Vec3 {
+        self.sun_direction
+    }
+
+    /// The Perez formula for the distribution of a quantity of the sky at
+    /// the angle `theta` to the zenith and the angle `gamma` to the sun.
+    fn perez(coefficients: Perez, cos_theta: f32, gamma: f32) -> f32 {
+        let [a, b, c, d, e] = coefficients;
+        let cos_gamma = gamma.cos();
+        (1.0 + a * (b / cos_theta).exp())
+            * (1.0 + c * (d * gamma).exp() + e * cos_gamma * cos_gamma)
+    }
+
+    /// The radiance of the sky without the sun that arrives from `direction`.
+    pub fn radiance(&self, direction: Vec3) -> Color {
+        // Below the horizon, the sky continues with its brightness at the
+        // horizon, which is seen where the ground ends.
+        let direction = direction.unit();
+        let cos_theta = direction.y().max(0.0);
+        let gamma = direction.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        // Scale the values at the zenith by the distribution relative to it.
+        let [luminance, x, y] = std::array::from_fn(|i| {
+            self.zenith[i] * Self::perez(self.perez[i], cos_theta, gamma)
+                / Self::perez(self.perez[i], 1.0, self.theta_sun)
+        });
+        let luminance = luminance / KCD_PER_UNIT;
+        let color = xyz_to_linear_srgb(Vec3::new(
+            x / y * luminance,
+            luminance,
+            (1.0 - x - y) / y * luminance,
+        ));
+        Color::new(color.r().max(0.0), color.g().max(0.0), color.b().max(0.0))
+    }
+
+    /// The fraction of the light of the sun at `wavelength` in nanometers
+    /// that passes through the atmosphere, due to scattering by molecules and
+    /// aerosols.
+    fn sun_transmittance(&self, wavelength: f32) -> f32 {
+        // The relative length of the path through the atmosphere.
+        let theta_degrees = self.theta_sun * 180.0 / PI;
+        let optical_mass =
+            1.0 / (self.theta_sun.cos() + 0.15 * (93.885 - theta_degrees).powf(-1.253));
+
+        let lambda = wavelength / 1000.0;
+        let rayleigh = 0.008735 * lambda.powf(-4.08);
+        let beta = 0.046_083_66 * self.turbidity - 0.045_860_26;
+        let aerosol = beta * lambda.powf(-1.3);
+        (-optical_mass * (rayleigh + aerosol)).exp()
+    }
+
+    /// The disk of the sun, whose light is reddened by the atmosphere when
+    /// it is low.
+    pub fn sun(&self) -> SunLight {
+        let radiance = if self.sun_direction.y() > 0.0 {
+            spectrum_to_rgb(|wavelength| self.sun_transmittance(wavelength)) * SUN_RADIANCE
+        } else {
+            Color::black()
+        };
+        SunLight::new(-self.sun_direction, SUN_ANGULAR_DIAMETER, radiance)
+    }
+
+    /// Render the sky without the sun into an environment map that is `width`
+    /// pixels wide and half as high.
+    pub fn to_environment(&self, width: u32) -> EnvironmentMap {
+        let height = (width / 2).max(1);
+        let image = Rgb32FImage::from_fn(width, height, |x, y| {
+            let phi = 2.0 * PI * (x as f32 + 0.5) / width as f32;
+            let theta = PI * (y as f32 + 0.5) / height as f32;
+            let color = self.radiance(equirectangular_direction(phi, theta));
+            image::Rgb([color.r(), color.g(), color.b()])
+        });
+        EnvironmentMap::from_image(image)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        hittable::HitRecord,
+        light::Light,
+        material::{Conductor, Material, MixMaterial},
+        point::Point,
+        principled::PrincipledBuilder,
+        ray::Ray,
+        vec3::Vec3,
+    };
+
+    use super::{Sky, KCD_PER_UNIT};
+
+    #[test]
+    fn sky_follows_the_sun() {
+        let noon = Sky::new(60.0, 0.0, 3.0);
+        let dusk = Sky::new(5.0, 0.0, 3.0);
+
+        // The zenith has the luminance of the model.
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let zenith = noon.radiance(up).luminance() * KCD_PER_UNIT;
+        assert!((zenith - noon.zenith[0]).abs() < 0.01 * noon.zenith[0]);
+
+        // The sky around the sun is brighter than the sky opposite of it.
+        let towards = Vec3::new(0.0, 0.3, -1.0);
+        let away = Vec3::new(0.0, 0.3, 1.0);
+        assert!(noon.radiance(towards).luminance() > noon.radiance(away).luminance());
+
+        // The low sun is dimmer and redder.
+        let sun = |sky: &Sky| sky.sun().radiance(sky.sun_direction());
+        let (high, low) = (sun(&noon), sun(&dusk));
+        assert!(low.luminance() < high.luminance());
+        assert!(low.r() / low.b() > high.r() / high.b());
+    }
+
+    #[test]
+    fn glossy_materials_weigh_the_sun() {
+        // Light sampling skips lights that materials cannot weigh, so glossy
+        // materials must know the density of the directions towards the sun.
+        let sky = Sky::new(45.0, 0.0, 3.0);
+        let sun = sky.sun();
+        let glossy = Arc::new(PrincipledBuilder::new().roughness(0.5).build());
+        let materials: [Arc<dyn Material>; 2] = [
+            Arc::new(Conductor::gold(0.5)),
+            Arc::new(MixMaterial::from_weight(
+                Arc::new(Conductor::copper(0.3)),
+                glossy,
+                0.5,
+            )),
+        ];
+        let ray = Ray::new(Point::new(0.0, 1.0, -1.0), Vec3::new(0.0, -1.0, 1.0), 0.0);
+        for material in materials {
+            let hit_record = HitRecord::new(
+                &ray,
+                Point::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0).unit(),
+                1.0,
+                0.0,
+                0.0,
+                material.clone(),
+            );
+            let (direction, _, _) = sun.sample(hit_record.p()).unwrap();
+            assert!(sun.pdf(hit_record.p(), direction) > 0.0);
+            assert!(material.eval(&ray, &hit_record, direction).luminance() > 0.0);
+            assert!(material.pdf(&ray, &hit_record, direction) > 0.0);
+        }
+    }
+}
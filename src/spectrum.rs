@@ -0,0 +1,381 @@
+//! This module contains the code to render with wavelengths of light instead
+//! of RGB colors, e.g., for dispersion. A ray that carries a wavelength
+//! contributes to the image according to the color a human perceives for this
+//! wavelength, see [wavelength_to_rgb].
+//!
+//! In spectral mode, each camera sample carries several wavelengths at once,
+//! see [SampledWavelengths], and the light along the path is a
+//! [SampledSpectrum]. RGB colors of textures, materials, and lights are
+//! upsampled to spectra with [rgb_to_spectrum].
+
+use std::{
+    ops::{Add, AddAssign, Mul},
+    sync::LazyLock,
+};
+
+use crate::{color::Color, vec3::Vec3};
+
+/// The shortest wavelength in nanometers that is rendered.
+pub const LAMBDA_MIN: f32 = 360.0;
+
+/// The longest wavelength in nanometers that is rendered.
+pub const LAMBDA_MAX: f32 = 830.0;
+
+/// The number of wavelengths that are sampled per camera sample in spectral
+/// mode.
+pub const SPECTRUM_SAMPLES: usize = 4;
+
+/// The integrals of the color matching functions over the visible
+/// wavelengths. They are used to normalize spectra such that a constant
+/// spectrum of 1 has luminance 1.
+static CIE_INTEGRAL: LazyLock<Vec3> = LazyLock::new(|| {
+    let steps = (LAMBDA_MAX - LAMBDA_MIN) as usize;
+    (0..steps).fold(Vec3::new(0.0, 0.0, 0.0), |sum, i| {
+        sum + cie_xyz(LAMBDA_MIN + i as f32 + 0.5)
+    })
+});
+
+/// The X coordinate of the D65 white point, i.e., of the sRGB white, whose
+/// Y coordinate is 1.
+const D65_WHITE_X: f32 = 0.950_47;
+
+/// The Z coordinate of the D65 white point, i.e., of the sRGB white, whose
+/// Y coordinate is 1.
+const D65_WHITE_Z: f32 = 1.088_83;
+
+/// The start of the wavelength bins of [SMITS_BASIS] in nanometers.
+const SMITS_LAMBDA_START: f32 = 380.0;
+
+/// The width of the wavelength bins of [SMITS_BASIS] in nanometers.
+const SMITS_LAMBDA_STEP: f32 = 34.0;
+
+/// The reflectance spectra of white, cyan, magenta, yellow, red, green, and
+/// blue from Smits, "An RGB-to-Spectrum Conversion for Reflectances". Each
+/// spectrum is sampled at ten bins from 380 to 720 nanometers.
+const SMITS_BASIS: [[f32; 10]; 7] = [
+    [1.0, 1.0, 0.9999, 0.9993, 0.9992, 0.9998, 1.0, 1.0, 1.0, 1.0],
+    [
+        0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0, 0.0, 0.0,
+    ],
+    [
+        1.0, 1.0, 0.9685, 0.2229, 0.0, 0.0458, 0.8369, 1.0, 1.0, 0.9959,
+    ],
+    [
+        0.0001, 0.0, 0.1088, 0.6651, 1.0, 1.0, 0.9996, 0.9586, 0.9685, 0.9840,
+    ],
+    [
+        0.1012, 0.0515, 0.0, 0.0, 0.0, 0.0, 0.8325, 1.0149, 1.0149, 1.0149,
+    ],
+    [
+        0.0, 0.0, 0.0273, 0.7937, 1.0, 0.9418, 0.1719, 0.0, 0.0, 0.0025,
+    ],
+    [
+        1.0, 1.0, 0.8916, 0.3323, 0.0, 0.0, 0.0003, 0.0369, 0.0483, 0.0496,
+    ],
+];
+
+/// Sample a wavelength in nanometers from the visible range given a uniform
+/// random number `u` in `[0, 1)`. Wavelengths are chosen with a density
+/// proportional to how well humans can perceive them, which reduces color
+/// noise compared to sampling them uniformly.
+pub fn sample_visible_wavelength(u: f32) -> f32 {
+    538.0 - 138.888_89 * (0.856_910_6 - 1.827_502 * u).atanh()
+}
+
+/// The probability density of sampling `wavelength` by
+/// [sample_visible_wavelength].
+pub fn visible_wavelength_pdf(wavelength: f32) -> f32 {
+    if !(LAMBDA_MIN..=LAMBDA_MAX).contains(&wavelength) {
+        return 0.0;
+    }
+    let cosh = (0.0072 * (wavelength - 538.0)).cosh();
+    0.003_939_804 / (cosh * cosh)
+}
+
+/// Evaluate the CIE 1931 color matching functions at `wavelength` in
+/// nanometers. Returns the `X`, `Y`, and `Z` response as a [Vec3].
+///
+/// Uses the multi-lobe fit by Wyman et al., "Simple Analytic Approximations
+/// to the CIE XYZ Color Matching Functions".
+pub fn cie_xyz(wavelength: f32) -> Vec3 {
+    let lobe = |mu: f32, sigma_below: f32, sigma_above: f32| {
+        let sigma = if wavelength < mu {
+            sigma_below
+        } else {
+            sigma_above
+        };
+        let t = (wavelength - mu) / sigma;
+        (-0.5 * t * t).exp()
+    };
+    let x = 1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+        - 0.065 * lobe(501.1, 20.4, 26.2);
+    let y = 0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1);
+    let z = 1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8);
+    Vec3::new(x, y, z)
+}
+
+/// Convert a color from CIE XYZ to linear sRGB. Colors outside of the sRGB
+/// gamut have negative components.
+pub fn xyz_to_linear_srgb(xyz: Vec3) -> Color {
+    let (x, y, z) = (xyz.x(), xyz.y(), xyz.z());
+    Color::new(
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    )
+}
+
+/// Convert the XYZ coordinates of a spectrum, which are normalized such that
+/// a constant spectrum of 1 has `Y = 1`, to linear sRGB. The color is white
+/// balanced such that an equal energy spectrum is white.
+pub fn spectral_xyz_to_rgb(xyz: Vec3) -> Color {
+    // Scale the equal energy white point onto the D65 white point of sRGB.
+    let integral = *CIE_INTEGRAL;
+    xyz_to_linear_srgb(Vec3::new(
+        xyz.x() * D65_WHITE_X * integral.y() / integral.x(),
+        xyz.y(),
+        xyz.z() * D65_WHITE_Z * integral.y() / integral.z(),
+    ))
+}
+
+/// The contribution of light with a single `wavelength` sampled by
+/// [sample_visible_wavelength] to the RGB image. The color is divided by the
+/// probability of the sample and white balanced, such that the average over
+/// many samples of an equal energy spectrum is white.
+pub fn wavelength_to_rgb(wavelength: f32) -> Color {
+    let pdf = visible_wavelength_pdf(wavelength);
+    if pdf == 0.0 {
+        return Color::black();
+    }
+    spectral_xyz_to_rgb(cie_xyz(wavelength) / (pdf * CIE_INTEGRAL.y()))
+}
+
+/// Evaluate a smooth spectrum for the RGB `color` at `wavelength` in
+/// nanometers. The spectrum is a combination of the basis spectra by Smits,
+/// such that white is the constant spectrum 1, and it scales linearly with
+/// the color. Negative components of the color are ignored.
+pub fn rgb_to_spectrum(color: Color, wavelength: f32) -> f32 {
+    // Linearly interpolate the basis spectra between the centers of the bins.
+    let x = ((wavelength - SMITS_LAMBDA_START) / SMITS_LAMBDA_STEP - 0.5).clamp(0.0, 9.0);
+    let i = (x as usize).min(8);
+    let t = x - i as f32;
+    let basis =
+        |spectrum: usize| (1.0 - t) * SMITS_BASIS[spectrum][i] + t * SMITS_BASIS[spectrum][i + 1];
+    let [white, cyan, magenta, yellow, red, green, blue] = [0, 1, 2, 3, 4, 5, 6];
+
+    let (r, g, b) = (color.r().max(0.0), color.g().max(0.0), color.b().max(0.0));
+    if r <= g && r <= b {
+        // White plus cyan plus either green or blue.
+        let value = r * basis(white);
+        if g <= b {
+            value + (g - r) * basis(cyan) + (b - g) * basis(blue)
+        } else {
+            value + (b - r) * basis(cyan) + (g - b) * basis(green)
+        }
+    } else if g <= r && g <= b {
+        // White plus magenta plus either red or blue.
+        let value = g * basis(white);
+        if r <= b {
+            value + (r - g) * basis(magenta) + (b - r) * basis(blue)
+        } else {
+            value + (b - g) * basis(magenta) + (r - b) * basis(red)
+        }
+    } else {
+        // White plus yellow plus either red or green.
+        let value = b * basis(white);
+        if r <= g {
+            value + (r - b) * basis(yellow) + (g - r) * basis(green)
+        } else {
+            value + (g - b) * basis(yellow) + (r - g) * basis(red)
+        }
+    }
+}
+
+/// The number of wavelengths at which [spectrum_to_rgb] evaluates a spectrum.
+const SPECTRUM_TO_RGB_STEPS: usize = 48;
+
+/// The wavelengths at which [spectrum_to_rgb] evaluates a spectrum, together
+/// with the normalized color matching functions at these wavelengths.
+static SPECTRUM_TO_RGB_WEIGHTS: LazyLock<[(f32, Vec3); SPECTRUM_TO_RGB_STEPS]> =
+    LazyLock::new(|| {
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / SPECTRUM_TO_RGB_STEPS as f32;
+        std::array::from_fn(|i| {
+            let wavelength = LAMBDA_MIN + (i as f32 + 0.5) * step;
+            (wavelength, cie_xyz(wavelength) * (step / CIE_INTEGRAL.y()))
+        })
+    });
+
+/// Convert a reflectance `spectrum`, given as a function of the wavelength in
+/// nanometers, to a linear sRGB color. The spectrum is integrated against the
+/// color matching functions, such that the constant spectrum 1 is white.
+pub fn spectrum_to_rgb(spectrum: impl Fn(f32) -> f32) -> Color {
+    let xyz = SPECTRUM_TO_RGB_WEIGHTS
+        .iter()
+        .fold(Vec3::new(0.0, 0.0, 0.0), |sum, (wavelength, weight)| {
+            sum + *weight * spectrum(*wavelength)
+        });
+    spectral_xyz_to_rgb(xyz)
+}
+
+#[derive(Clone, Copy, Debug)]
+/// The wavelengths that are carried by a camera sample in spectral mode. The
+/// first wavelength is the hero wavelength, which determines the direction
+/// of wavelength dependent scattering, and the others are spread evenly.
+pub struct SampledWavelengths {
+    /// The wavelengths in nanometers.
+    lambda: [f32; SPECTRUM_SAMPLES],
+    /// The probability densities of the wavelengths. A density of zero marks
+    /// a terminated wavelength.
+    pdf: [f32; SPECTRUM_SAMPLES],
+}
+
+impl SampledWavelengths {
+    /// Sample the wavelengths from the visible range given a uniform random
+    /// number `u` in `[0, 1)` for the hero wavelength.
+    pub fn sample_visible(u: f32) -> Self {
+        let mut lambda = [0.0; SPECTRUM_SAMPLES];
+        let mut pdf = [0.0; SPECTRUM_SAMPLES];
+        for i in 0..SPECTRUM_SAMPLES {
+            let u = (u + i as f32 / SPECTRUM_SAMPLES as f32).fract();
+            lambda[i] = sample_visible_wavelength(u);
+            pdf[i] = visible_wavelength_pdf(lambda[i]);
+        }
+        Self { lambda, pdf }
+    }
+
+    #[inline]
+    /// The hero wavelength in nanometers.
+    pub fn hero(&self) -> f32 {
+        self.lambda[0]
+    }
+
+    #[inline]
+    /// The wavelength at index `i` in nanometers.
+    pub fn lambda(&self, i: usize) -> f32 {
+        self.lambda[i]
+    }
+
+    #[inline]
+    /// The probability density of the wavelength at index `i`.
+    pub fn pdf(&self, i: usize) -> f32 {
+        self.pdf[i]
+    }
+
+    /// Terminate all but the hero wavelength. This is necessary when the
+    /// path scatters in a direction that only applies to the hero wavelength,
+    /// e.g., at a dispersive dielectric.
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+        self.pdf[1..].fill(0.0);
+        self.pdf[0] /= SPECTRUM_SAMPLES as f32;
+    }
+
+    /// Whether only the hero wavelength is left.
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// The values of a spectrum at [SampledWavelengths], e.g., of the light that
+/// is carried along a path.
+pub struct SampledSpectrum([f32; SPECTRUM_SAMPLES]);
+
+impl SampledSpectrum {
+    /// Create a spectrum with the same value at all wavelengths.
+    pub fn constant(value: f32) -> Self {
+        Self([value; SPECTRUM_SAMPLES])
+    }
+
+    /// Upsample the RGB `color` to a spectrum at the `wavelengths`, see
+    /// [rgb_to_spectrum].
+    pub fn from_rgb(color: Color, wavelengths: &SampledWavelengths) -> Self {
+        Self(
+            wavelengths
+                .lambda
+                .map(|lambda| rgb_to_spectrum(color, lambda)),
+        )
+    }
+
+    /// Compute the XYZ coordinates of the spectrum. The coordinates are
+    /// normalized such that a constant spectrum of 1 has `Y = 1`.
+    pub fn to_xyz(&self, wavelengths: &SampledWavelengths) -> Vec3 {
+        let sum = (0..SPECTRUM_SAMPLES)
+            .filter(|&i| wavelengths.pdf(i) > 0.0)
+            .fold(Vec3::new(0.0, 0.0, 0.0), |sum, i| {
+                sum + self.0[i] * cie_xyz(wavelengths.lambda(i)) / wavelengths.pdf(i)
+            });
+        sum / (SPECTRUM_SAMPLES as f32 * CIE_INTEGRAL.y())
+    }
+
+    /// Convert the spectrum to linear sRGB via its XYZ coordinates, see
+    /// [spectral_xyz_to_rgb].
+    pub fn to_rgb(&self, wavelengths: &SampledWavelengths) -> Color {
+        spectral_xyz_to_rgb(self.to_xyz(wavelengths))
+    }
+}
+
+impl Add for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(std::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl AddAssign for SampledSpectrum {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = SampledSpectrum;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(std::array::from_fn(|i| self.0[i] * rhs.0[i]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::color::Color;
+
+    use super::{
+        rgb_to_spectrum, sample_visible_wavelength, wavelength_to_rgb, SampledSpectrum,
+        SampledWavelengths, LAMBDA_MAX, LAMBDA_MIN,
+    };
+
+    #[test]
+    fn wavelengths_average_to_white() {
+        let samples = 10000;
+        let sum = (0..samples).fold(Color::black(), |sum, i| {
+            let u = (i as f32 + 0.5) / samples as f32;
+            let wavelength = sample_visible_wavelength(u);
+            assert!((LAMBDA_MIN..=LAMBDA_MAX).contains(&wavelength));
+            sum + wavelength_to_rgb(wavelength)
+        }) * (1.0 / samples as f32);
+        assert!((sum.r() - 1.0).abs() < 0.01);
+        assert!((sum.g() - 1.0).abs() < 0.01);
+        assert!((sum.b() - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn upsampled_white_stays_white() {
+        for wavelength in [400.0, 500.0, 600.0, 700.0] {
+            assert!((rgb_to_spectrum(Color::white(), wavelength) - 1.0).abs() < 1e-3);
+        }
+
+        // Average the film response to a white spectrum over many samples.
+        let samples = 1000;
+        let sum = (0..samples).fold(Color::black(), |sum, i| {
+            let wavelengths = SampledWavelengths::sample_visible(i as f32 / samples as f32);
+            sum + SampledSpectrum::from_rgb(Color::white(), &wavelengths).to_rgb(&wavelengths)
+        }) * (1.0 / samples as f32);
+        assert!((sum.r() - 1.0).abs() < 0.01);
+        assert!((sum.g() - 1.0).abs() < 0.01);
+        assert!((sum.b() - 1.0).abs() < 0.01);
+    }
+}
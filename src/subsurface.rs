@@ -0,0 +1,255 @@
+//! This module contains the code related to subsurface scattering, i.e.,
+//! translucent objects like skin, marble, or wax where light enters the
+//! surface, scatters around inside of the object, and leaves it somewhere
+//! else.
+
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    color::Color,
+    hittable::{HitRecord, Hittable, Span},
+    interval::Interval,
+    material::Material,
+    onb::ONB,
+    random_0_1_f32, random_cosine_direction, random_unit_vector,
+    ray::Ray,
+};
+
+/// The maximum number of scattering events inside of a [Subsurface] object.
+/// Walks that do not find their way out by then are absorbed.
+const MAX_WALK_STEPS: u32 = 256;
+
+#[derive(Debug, Clone)]
+/// A translucent object whose inside is filled with a scattering medium. Like
+/// in a [ConstantMedium](crate::constant_medium::ConstantMedium), the distance
+/// that light travels between two scattering events is sampled from an
+/// exponential distribution. But the light is not scattered at a single point
+/// inside of the medium. Instead, it performs a random walk until it leaves
+/// the boundary again, such that light bleeds softly through the object.
+/// The material of the boundary is ignored. The boundary may consist of
+/// several parts, which may overlap. The walk only leaves the object where
+/// it leaves the last of the parts that it is inside of.
+///
+/// The walk only finds light with the ray that leaves the object. Lights that
+/// such rays cannot hit, i.e., a [PointLight](crate::light::PointLight), a
+/// [SpotLight](crate::light::SpotLight), or a
+/// [DirectionalLight](crate::light::DirectionalLight), do not light the
+/// object. Use area lights instead.
+///
+/// The walk traces the boundary in the coordinates of the incoming ray. Thus,
+/// transform the boundary instead of wrapping the [Subsurface] object into a
+/// transformation like [Translate](crate::hittable::Translate).
+pub struct Subsurface {
+    boundary: Arc<dyn Hittable>,
+    walk: Arc<RandomWalk>,
+}
+
+impl Subsurface {
+    /// Create a new subsurface scattering object.
+    ///
+    /// * `boundary` - The closed hittable which encloses the medium.
+    /// * `mean_free_path` - The average distance that light of each color
+    ///   channel travels between two scattering events. A larger mean free path
+    ///   makes the object more translucent, e.g., light that is red travels
+    ///   furthest in skin.
+    /// * `albedo` - The fraction of light of each color channel which survives
+    ///   a scattering event.
+    pub fn new(boundary: Arc<dyn Hittable>, mean_free_path: Color, albedo: Color) -> Self {
+        let walk = RandomWalk {
+            boundary: Arc::clone(&boundary),
+            mean_free_path: [mean_free_path.r(), mean_free_path.g(), mean_free_path.b()],
+            albedo,
+        };
+        Self {
+            boundary,
+            walk: Arc::new(walk),
+        }
+    }
+}
+
+impl Hittable for Subsurface {
+    fn hit(&self, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let mut hit_record = self.boundary.hit(ray, ray_t)?;
+        hit_record.set_material(self.walk.clone());
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &AABB {
+        self.boundary.bounding_box()
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        self.boundary.spans(ray)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// The material of a [Subsurface] object, which walks through the inside of
+/// the boundary.
+struct RandomWalk {
+    boundary: Arc<dyn Hittable>,
+    mean_free_path: [f32; 3],
+    albedo: Color,
+}
+
+impl RandomWalk {
+    /// Sample the distance to the next scattering event with the mean free
+    /// path of a random color channel.
+    fn sample_distance(&self) -> f32 {
+        let channel = ((random_0_1_f32() * 3.0) as usize).min(2);
+        -self.mean_free_path[channel] * (1.0 - random_0_1_f32()).ln()
+    }
+
+    /// Weigh a step over `distance` by the probability of the step for each
+    /// color channel over the probability of sampling it with
+    /// [RandomWalk::sample_distance]. A step ends either in a scattering
+    /// event, or it leaves the medium before the next event.
+    fn step_weight(&self, distance: f32, scattered: bool) -> Color {
+        let probabilities = self.mean_free_path.map(|mean_free_path| {
+            let transmittance = (-distance / mean_free_path).exp();
+            if scattered {
+                transmittance / mean_free_path
+            } else {
+                transmittance
+            }
+        });
+        let pdf = probabilities.iter().sum::<f32>() / 3.0;
+        if pdf <= 0.0 {
+            return Color::black();
+        }
+        Color::new(probabilities[0], probabilities[1], probabilities[2]) * (1.0 / pdf)
+    }
+}
+
+impl Material for RandomWalk {
+    fn scatter(&self, ray: &Ray, hit_record: HitRecord) -> Option<(Ray, Color)> {
+        if !hit_record.front_face() {
+            // The ray started inside of the object, let it leave unchanged.
+            let scattered = ray.spawn(hit_record.p(), *ray.direction());
+            return Some((scattered, Color::white()));
+        }
+
+        // Enter the object diffusely.
+        let inward = -hit_record.normal();
+        let mut p = hit_record.p();
+        let mut direction = ONB::new(inward).to_world(random_cosine_direction());
+        let mut attenuation = Color::white();
+
+        // Only the first step starts on the boundary. Later steps start at
+        // scattering events inside of the object, which may lie arbitrarily
+        // close to the boundary, so they must not skip nearby exits.
+        let mut t_min = 0.0001;
+        // The number of parts of the boundary that the walk is inside of.
+        // Front faces lead into a part and back faces out of it.
+        let mut depth = 1;
+        for _ in 0..MAX_WALK_STEPS {
+            let distance = self.sample_distance();
+            let step = ray.spawn(p, direction);
+            let mut t = t_min;
+            while let Some(crossing) = self.boundary.hit(&step, Interval::new(t, distance)) {
+                t = crossing.t() + 0.0001;
+                if crossing.front_face() {
+                    depth += 1;
+                    continue;
+                }
+                depth -= 1;
+                if depth > 0 {
+                    continue;
+                }
+
+                // Leave the object diffusely.
+                attenuation = attenuation * self.step_weight(crossing.t(), false);
+                let outward = crossing.outward_normal();
+                let direction = ONB::new(outward).to_world(random_cosine_direction());
+                return Some((ray.spawn(crossing.p(), direction), attenuation));
+            }
+
+            attenuation = attenuation * self.step_weight(distance, true) * self.albedo;
+            p = step.at(distance);
+            direction = *random_unit_vector();
+            t_min = 0.0;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        color::Color,
+        hittable::{Hittable, Sphere, World},
+        interval::Interval,
+        light::{Light, PointLight},
+        material::Lambertian,
+        point::Point,
+        ray::Ray,
+        vec3::Vec3,
+    };
+
+    use super::Subsurface;
+
+    #[test]
+    fn walk_leaves_the_boundary() {
+        let sphere = Sphere::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::white())),
+        );
+        let subsurface = Subsurface::new(Arc::new(sphere), Color::white() * 0.2, Color::white());
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        for _ in 0..100 {
+            let hit_record = subsurface.hit(&ray, Interval::universe()).unwrap();
+            let (scattered, attenuation) = hit_record
+                .material()
+                .scatter(&ray, hit_record.copy())
+                .unwrap();
+            let origin = *scattered.origin();
+            assert!((origin.length() - 1.0).abs() < 0.001);
+            assert!(scattered.direction().dot(*origin) > 0.0);
+            assert!((attenuation.g() - 1.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn walk_is_not_lit_by_delta_lights() {
+        let sphere = Sphere::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::white())),
+        );
+        let subsurface = Subsurface::new(Arc::new(sphere), Color::white() * 0.2, Color::white());
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit_record = subsurface.hit(&ray, Interval::universe()).unwrap();
+        let light = PointLight::new(Point::new(0.0, 0.0, 3.0), Color::white());
+        let (direction, _, radiance) = light.sample(hit_record.p()).unwrap();
+        assert!(radiance.luminance() > 0.0);
+        let f = hit_record.material().eval(&ray, &hit_record, direction);
+        assert_eq!(f.luminance(), 0.0);
+    }
+
+    #[test]
+    fn walk_leaves_overlapping_parts_on_the_outside() {
+        let material = Arc::new(Lambertian::new(Color::white()));
+        let centers = [Point::new(-0.5, 0.0, 0.0), Point::new(0.5, 0.0, 0.0)];
+        let mut parts = World::new();
+        for center in centers {
+            parts.push(Arc::new(Sphere::new(center, 1.0, material.clone())));
+        }
+        let subsurface = Subsurface::new(Arc::new(parts), Color::white() * 0.5, Color::white());
+        let ray = Ray::new(Point::new(-3.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        for _ in 0..200 {
+            let hit_record = subsurface.hit(&ray, Interval::universe()).unwrap();
+            let (scattered, _) = hit_record
+                .material()
+                .scatter(&ray, hit_record.copy())
+                .unwrap();
+            // The walk leaves the surface of one sphere outside of the other.
+            let distances = centers.map(|center| (*scattered.origin() - center).length());
+            let on_surface = distances.iter().any(|d| (d - 1.0).abs() < 0.001);
+            assert!(on_surface && distances.iter().all(|d| *d > 0.999));
+        }
+    }
+}
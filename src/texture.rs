@@ -108,6 +108,20 @@ impl ImageTexture {
         }
     }
 
+    /// Loads the alpha channel of the image at the given path as a grayscale
+    /// texture, where transparent is black and opaque is white. Panics if the
+    /// loading fails.
+    pub fn from_alpha(path: impl AsRef<Path>) -> Self {
+        let image = image::open(path)
+            .expect("Failed to load image.")
+            .into_rgba8();
+        let image = ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+            let alpha = image.get_pixel(x, y)[3];
+            Rgb([alpha, alpha, alpha])
+        });
+        Self { image }
+    }
+
     #[inline]
     /// Clamp x into [low, high].
     fn clamp(x: u32, low: u32, high: u32) -> u32 {
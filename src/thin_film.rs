@@ -0,0 +1,182 @@
+//! This module contains the code related to thin-film interference, which
+//! causes the iridescent colors of soap bubbles and oil slicks. Light that is
+//! reflected at the top of a thin transparent film interferes with light that
+//! is reflected at its bottom. Whether they amplify or cancel each other
+//! depends on the wavelength, the thickness of the film, and the angle.
+
+use std::sync::Arc;
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    spectrum::{rgb_to_spectrum, spectrum_to_rgb},
+    texture::{SolidColor, Texture},
+    PI,
+};
+
+#[derive(Debug, Clone)]
+/// A thin film on top of a surface, see [Dielectric::with_thin_film] and
+/// [Metal::with_thin_film].
+///
+/// [Dielectric::with_thin_film]: crate::material::Dielectric::with_thin_film
+/// [Metal::with_thin_film]: crate::material::Metal::with_thin_film
+pub struct ThinFilm {
+    thickness: Arc<dyn Texture>,
+    max_thickness: f32,
+    refraction_index: f32,
+}
+
+impl ThinFilm {
+    /// Create a new film of constant thickness.
+    ///
+    /// * `thickness` - The thickness of the film in nanometers. Films between
+    ///   100 and 1000 nanometers show the strongest colors.
+    /// * `refraction_index` - The refractive index of the film, e.g., 1.33 for
+    ///   the water of a soap bubble.
+    pub fn new(thickness: f32, refraction_index: f32) -> Self {
+        Self::from_texture(
+            Arc::new(SolidColor::new(Color::white())),
+            thickness,
+            refraction_index,
+        )
+    }
+
+    /// Create a new film whose thickness varies over the surface.
+    ///
+    /// * `thickness` - The texture whose luminance scales the thickness.
+    /// * `max_thickness` - The thickness in nanometers where the texture is
+    ///   white.
+    /// * `refraction_index` - The refractive index of the film.
+    pub fn from_texture(
+        thickness: Arc<dyn Texture>,
+        max_thickness: f32,
+        refraction_index: f32,
+    ) -> Self {
+        Self {
+            thickness,
+            max_thickness,
+            refraction_index,
+        }
+    }
+
+    /// Compute the reflectance of the film on top of a `substrate` for light
+    /// coming from a medium with refractive index `eta_i` at the angle
+    /// `cos_theta_i` to the normal. For light of a single `wavelength`, all
+    /// color channels are the same. Otherwise, the reflectance is integrated
+    /// over the visible spectrum.
+    pub(crate) fn reflectance(
+        &self,
+        hit_record: &HitRecord,
+        cos_theta_i: f32,
+        eta_i: f32,
+        substrate: Substrate,
+        wavelength: Option<f32>,
+    ) -> Color {
+        let thickness = self
+            .thickness
+            .value(hit_record.u(), hit_record.v(), hit_record.p())
+            .luminance()
+            * self.max_thickness;
+        let reflectance =
+            |wavelength| self.airy(cos_theta_i, eta_i, thickness, substrate, wavelength);
+        match wavelength {
+            Some(wavelength) => Color::white() * reflectance(wavelength),
+            None => spectrum_to_rgb(reflectance),
+        }
+    }
+
+    /// The reflectance of the film for a single `wavelength`, which sums up
+    /// the light reflected after any number of passes through the film
+    /// (Airy summation). The result is averaged over both polarizations.
+    fn airy(
+        &self,
+        cos_theta_i: f32,
+        eta_i: f32,
+        thickness: f32,
+        substrate: Substrate,
+        wavelength: f32,
+    ) -> f32 {
+        let eta_f = self.refraction_index;
+        let Some((r12_s, r12_p, cos_theta_f)) = fresnel_amplitudes(cos_theta_i, eta_i, eta_f)
+        else {
+            return 1.0;
+        };
+        let (r23_s, r23_p) = match substrate {
+            Substrate::Dielectric(eta_t) => match fresnel_amplitudes(cos_theta_f, eta_f, eta_t) {
+                Some((r23_s, r23_p, _)) => (r23_s, r23_p),
+                // Light that cannot leave the film is eventually reflected.
+                None => return 1.0,
+            },
+            Substrate::Mirror(color) => {
+                // A mirror flips the phase like a perfect conductor.
+                let r = rgb_to_spectrum(color, wavelength).clamp(0.0, 1.0).sqrt();
+                (-r, r)
+            }
+        };
+
+        // The phase difference between the light reflected at the top and
+        // the light that passed through the film once more.
+        let phase = 4.0 * PI * eta_f * thickness * cos_theta_f / wavelength;
+        let airy = |r12: f32, r23: f32| {
+            let interference = 2.0 * r12 * r23 * phase.cos();
+            (r12 * r12 + r23 * r23 + interference) / (1.0 + r12 * r12 * r23 * r23 + interference)
+        };
+        0.5 * (airy(r12_s, r23_s) + airy(r12_p, r23_p))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// The surface below a [ThinFilm].
+pub(crate) enum Substrate {
+    /// A dielectric with the given refractive index.
+    Dielectric(f32),
+    /// A mirror which reflects the fraction given by the color.
+    Mirror(Color),
+}
+
+/// The Fresnel amplitude coefficients for the s and p polarized parts of
+/// light which passes from a medium with refractive index `eta_i` into a
+/// medium with refractive index `eta_t` at the angle `cos_theta_i`. Also
+/// returns the cosine of the angle of the refracted light, or [None] if the
+/// light is totally reflected.
+fn fresnel_amplitudes(cos_theta_i: f32, eta_i: f32, eta_t: f32) -> Option<(f32, f32, f32)> {
+    let eta = eta_i / eta_t;
+    let sin2_theta_t = eta * eta * (1.0 - cos_theta_i * cos_theta_i).max(0.0);
+    if sin2_theta_t >= 1.0 {
+        return None;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+    let r_s =
+        (eta_i * cos_theta_i - eta_t * cos_theta_t) / (eta_i * cos_theta_i + eta_t * cos_theta_t);
+    let r_p =
+        (eta_t * cos_theta_i - eta_i * cos_theta_t) / (eta_t * cos_theta_i + eta_i * cos_theta_t);
+    Some((r_s, r_p, cos_theta_t))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::microfacet::fresnel_dielectric;
+
+    use super::{Substrate, ThinFilm};
+
+    #[test]
+    fn invisible_film_matches_fresnel() {
+        // A film with the refractive index of the substrate does not
+        // interfere with the reflection.
+        let film = ThinFilm::new(300.0, 1.5);
+        for cos_theta in [1.0, 0.7, 0.3] {
+            let reflectance = film.airy(cos_theta, 1.0, 300.0, Substrate::Dielectric(1.5), 550.0);
+            assert!((reflectance - fresnel_dielectric(cos_theta, 1.5)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn quarter_wave_film_cancels_reflection() {
+        // An anti-reflective coating on glass.
+        let eta_f = 1.5_f32.sqrt();
+        let thickness = 550.0 / (4.0 * eta_f);
+        let film = ThinFilm::new(thickness, eta_f);
+        let reflectance = film.airy(1.0, 1.0, thickness, Substrate::Dielectric(1.5), 550.0);
+        assert!(reflectance < 1e-4);
+    }
+}